@@ -1,11 +1,21 @@
 use crate::{
-    components::game::Game,
+    components::{
+        game::Game,
+        stats::{record_game, StatsPage},
+    },
     error_template::{AppError, ErrorTemplate},
-    game::{Card, Solitaire},
+    game::{GameRecord, GameState, History, Rules, SavedGame, Solitaire},
+    storage,
+};
+#[cfg(feature = "ssr")]
+use crate::{
+    db,
+    game::{solve, Card, Solution, VariantKind},
 };
 use leptos::*;
 use leptos_meta::*;
 use leptos_router::*;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 #[component]
@@ -23,9 +33,28 @@ pub fn App() -> impl IntoView {
             outside_errors.insert_with_default_key(AppError::NotFound);
             view! { <ErrorTemplate outside_errors /> }.into_view()
         }>
+            <nav>
+                <A href="/">"Klondike"</A>
+                <A href="/spider">"Spider"</A>
+                <A href="/freecell">"FreeCell"</A>
+                <A href="/saved">"Saved games"</A>
+                <A href="/stats">"Statistics"</A>
+            </nav>
             <main>
                 <Routes>
-                    <Route path="" view=Main />
+                    <Route path="" view=|| view! { <Main /> } />
+                    <Route path="/game/:seed" view=|| view! { <Main /> } />
+                    <Route
+                        path="/spider"
+                        view=|| view! { <Main variant="spider" /> }
+                    />
+                    <Route
+                        path="/freecell"
+                        view=|| view! { <Main variant="freecell" /> }
+                    />
+                    <Route path="/saved" view=SavedGames />
+                    <Route path="/saved/:id" view=LoadedGame />
+                    <Route path="/stats" view=StatsPage />
                 </Routes>
             </main>
         </Router>
@@ -33,41 +62,446 @@ pub fn App() -> impl IntoView {
 }
 
 #[component]
-fn Main() -> impl IntoView {
-    view! { <Solitaire /> }
+fn Main(
+    // Lets a route pick the variant instead of the `variant` query parameter
+    #[prop(optional)] variant: Option<&'static str>,
+) -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let seed = Signal::derive(move || {
+        params.with(|params| {
+            params.get("seed").and_then(|seed| seed.parse().ok())
+        })
+    });
+    let rules = Signal::derive(move || {
+        query.with(|query| {
+            Rules::from_query(|key| match (key, variant) {
+                ("variant", Some(variant)) => Some(variant.to_owned()),
+                _ => query.get(key).cloned(),
+            })
+        })
+    });
+    let winnable_only = Signal::derive(move || {
+        query.with(|query| {
+            query.get("winnable").is_some_and(|w| w == "true")
+        })
+    });
+    // Counts presses of "New game", so the same options still deal again
+    let new_games = Signal::derive(move || {
+        query.with(|query| query.get("deal").cloned())
+    });
+
+    view! { <Solitaire seed rules winnable_only new_games /> }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deal {
+    pub seed: u64,
+    pub winnable: bool,
+    pub state: GameState,
+}
+
+#[cfg(feature = "ssr")]
+const WINNABLE_TIME_BUDGET: std::time::Duration =
+    std::time::Duration::from_secs(5);
+#[cfg(feature = "ssr")]
+const SOLVER_NODE_BUDGET: usize = 100_000;
+
+#[cfg(feature = "ssr")]
+fn deal_winnable(
+    cards: Vec<Card>,
+    seed: Option<u64>,
+    rules: Rules,
+) -> Deal {
+    let started = std::time::Instant::now();
+    loop {
+        let state = GameState::new(cards.clone(), seed, rules);
+        let winnable = matches!(
+            solve(&state, SOLVER_NODE_BUDGET),
+            Solution::Winnable(_)
+        );
+
+        let out_of_time = started.elapsed() > WINNABLE_TIME_BUDGET;
+        if winnable || seed.is_some() || out_of_time {
+            log::info!("Dealt {} (winnable: {winnable})", state.seed);
+            return Deal {
+                seed: state.seed,
+                winnable,
+                state,
+            };
+        }
+    }
 }
 
 #[server]
-pub async fn fetch_cards() -> Result<Solitaire, ServerFnError> {
-    let cards = std::fs::read_dir(Path::new("public").join("cards"))
-        .expect("public/cards does not exist")
-        .map(|path| {
-            path.expect("failed to read card path")
-                .path()
-                .file_name()
-                .expect("failed to get card file name")
-                .to_str()
-                .expect("failed to convert card file name to string")
-                .to_owned()
+pub async fn fetch_cards(
+    seed: Option<u64>,
+    rules: Rules,
+    winnable_only: bool,
+) -> Result<Deal, ServerFnError> {
+    let cards: Vec<Card> =
+        std::fs::read_dir(Path::new("public").join("cards"))
+            .expect("public/cards does not exist")
+            .map(|path| {
+                path.expect("failed to read card path")
+                    .path()
+                    .file_name()
+                    .expect("failed to get card file name")
+                    .to_str()
+                    .expect("failed to convert card file name to string")
+                    .to_owned()
+            })
+            .filter(|card| !card.ends_with("joker.png"))
+            .filter(|card| card.ends_with(".png"))
+            .map(Card::new)
+            .collect();
+
+    // The solver only knows Klondike
+    if winnable_only && rules.variant == VariantKind::Klondike {
+        let deal = tokio::task::spawn_blocking(move || {
+            deal_winnable(cards, seed, rules)
+        })
+        .await?;
+        return Ok(deal);
+    }
+
+    let state = GameState::new(cards, seed, rules);
+    Ok(Deal {
+        seed: state.seed,
+        winnable: false,
+        state,
+    })
+}
+
+const SAVED_GAME_KEY: &str = "solitaire:saved-game";
+
+impl From<Deal> for SavedGame {
+    fn from(deal: Deal) -> Self {
+        SavedGame {
+            state: deal.state,
+            history: History::default(),
+            winnable: deal.winnable,
+        }
+    }
+}
+
+#[component]
+fn Solitaire(
+    seed: Signal<Option<u64>>,
+    rules: Signal<Rules>,
+    winnable_only: Signal<bool>,
+    new_games: Signal<Option<String>>,
+) -> impl IntoView {
+    // localStorage is only readable once hydrated, so hold off dealing
+    // until we know whether there is a game to resume
+    let checked_storage = create_rw_signal(false);
+    let saved = create_rw_signal(None::<SavedGame>);
+    let resumed = create_rw_signal(None::<SavedGame>);
+    create_effect(move |_| {
+        saved.set(storage::load(SAVED_GAME_KEY));
+        checked_storage.set(true);
+    });
+
+    let game = create_resource(
+        move || {
+            let waiting = !checked_storage()
+                || saved.with(Option::is_some)
+                || resumed.with(Option::is_some);
+            (!waiting)
+                .then(|| (seed(), rules(), winnable_only(), new_games()))
+        },
+        |deal| async move {
+            let (seed, rules, winnable_only, _) = deal?;
+            Some(fetch_cards(seed, rules, winnable_only).await.unwrap())
+        },
+    );
+
+    let navigate = use_navigate();
+    let new_deal = Callback::new(move |_| {
+        storage::remove(SAVED_GAME_KEY);
+        let was_resumed = resumed.get_untracked().is_some();
+        resumed.set(None);
+        match seed.get_untracked() {
+            Some(_) => navigate(
+                &format!(
+                    "/?{}&winnable={}",
+                    rules.get_untracked().query(),
+                    winnable_only.get_untracked()
+                ),
+                Default::default(),
+            ),
+            None if !was_resumed => game.refetch(),
+            None => {}
+        }
+    });
+
+    let current = move || {
+        resumed().or_else(|| game.get().flatten().map(SavedGame::from))
+    };
+
+    view! {
+        {move || {
+            saved()
+                .map(|saved_game| {
+                    view! { <ResumePrompt saved saved_game resumed /> }
+                })
+        }}
+        <Suspense fallback=move || {
+            view! { <div>"Loading..."</div> }
+        }>
+            {move || {
+                current()
+                    .map(|saved_game| {
+                        view! { <PersistedGame saved_game new_deal /> }
+                    })
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn PersistedGame(
+    saved_game: SavedGame,
+    new_deal: Callback<()>,
+) -> impl IntoView {
+    let winnable = saved_game.winnable;
+    let game = Solitaire::restore(saved_game);
+    let save_failed = create_rw_signal(None::<String>);
+    let persist = move || {
+        let snapshot = untrack(|| game.snapshot(winnable));
+        if snapshot.state.is_won() {
+            storage::remove(SAVED_GAME_KEY);
+        } else if snapshot.state.moves > 0 {
+            let result = storage::save(SAVED_GAME_KEY, &snapshot);
+            save_failed.set(result.err().map(|err| err.to_string()));
+        }
+    };
+    // Saving on every clock tick would rewrite the game once a second, so
+    // save after moves and catch the clock up when the page goes away
+    let progress = create_memo(move |_| game.progress());
+    create_effect(move |_| {
+        progress.track();
+        persist();
+    });
+    let pagehide = window_event_listener(ev::pagehide, move |_| persist());
+    on_cleanup(move || pagehide.remove());
+
+    // Undoing a winning move and playing it again mustn't count twice
+    let recorded = store_value(false);
+    let is_won = game.is_won;
+    create_effect(move |was_won: Option<bool>| {
+        let won = is_won();
+        if won && was_won == Some(false) && !recorded.get_value() {
+            record_game(game.record());
+            recorded.set_value(true);
+        }
+        won
+    });
+    // Dealing again or leaving for another game part way through counts
+    // as abandoning it
+    let abandon = move || {
+        let record = game.record();
+        if !recorded.get_value() && !record.is_won() && record.moves > 0 {
+            record_game(record);
+            recorded.set_value(true);
+            storage::remove(SAVED_GAME_KEY);
+        }
+    };
+    on_cleanup(abandon);
+    let new_deal = Callback::new(move |_| {
+        abandon();
+        new_deal(());
+    });
+
+    view! {
+        {move || {
+            save_failed()
+                .map(|err| {
+                    view! {
+                        <p class="save-failed" role="alert">
+                            "This game couldn't be saved: " {err}
+                        </p>
+                    }
+                })
+        }}
+        <Game game winnable new_deal />
+    }
+}
+
+#[component]
+fn ResumePrompt(
+    saved: RwSignal<Option<SavedGame>>,
+    saved_game: SavedGame,
+    resumed: RwSignal<Option<SavedGame>>,
+) -> impl IntoView {
+    let moves = saved_game.state.moves;
+    let record = GameRecord::new(&saved_game.state);
+    let resume = move |_| {
+        resumed.set(Some(saved_game.clone()));
+        saved.set(None);
+    };
+    let discard = move |_| {
+        record_game(record.clone());
+        storage::remove(SAVED_GAME_KEY);
+        saved.set(None);
+    };
+
+    view! {
+        <div class="resume">
+            <p>"You have a game in progress (" {moves} " moves)."</p>
+            <button on:click=resume>"Resume game"</button>
+            <button on:click=discard>"New deal"</button>
+        </div>
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGameSummary {
+    pub id: String,
+    pub seed: u64,
+    pub moves: u32,
+    pub score: i32,
+}
+
+#[server(SaveGame, "/api", "Cbor")]
+pub async fn save_game(
+    id: Option<String>,
+    game: SavedGame,
+) -> Result<String, ServerFnError> {
+    let id = tokio::task::spawn_blocking(move || {
+        let conn = db::connect()?;
+        db::save_game(&conn, id, &game)
+    })
+    .await??;
+
+    Ok(id)
+}
+
+#[server(LoadGame, "/api", "Cbor")]
+pub async fn load_game(id: String) -> Result<SavedGame, ServerFnError> {
+    let game = tokio::task::spawn_blocking(move || {
+        let conn = db::connect()?;
+        db::load_game(&conn, &id)
+    })
+    .await??;
+
+    Ok(game)
+}
+
+#[server(ListSavedGames, "/api", "Cbor")]
+pub async fn list_saved_games(
+) -> Result<Vec<SavedGameSummary>, ServerFnError> {
+    let games = tokio::task::spawn_blocking(move || {
+        let conn = db::connect()?;
+        db::list_games(&conn)
+    })
+    .await??;
+
+    Ok(games)
+}
+
+#[component]
+fn SavedGames() -> impl IntoView {
+    let games = create_resource(|| (), |_| list_saved_games());
+    let rows = move || {
+        games.get().map(|games| match games {
+            Ok(games) if games.is_empty() => {
+                view! { <p>"No saved games yet."</p> }.into_view()
+            }
+            Ok(games) => games
+                .into_iter()
+                .map(|game| {
+                    view! {
+                        <li>
+                            <A href=format!("/saved/{}", game.id)>
+                                {game.id.clone()}
+                            </A>
+                            " · Deal #"
+                            {game.seed}
+                            " · "
+                            {game.moves}
+                            " moves · score "
+                            {game.score}
+                        </li>
+                    }
+                })
+                .collect_view(),
+            Err(err) => {
+                view! { <p>"Couldn't list saved games: " {err.to_string()}</p> }
+                    .into_view()
+            }
         })
-        .filter(|card| !card.ends_with("joker.png"))
-        .filter(|card| card.ends_with(".png"))
-        .map(Card::new)
-        .collect();
+    };
 
-    Ok(Solitaire::new(cards))
+    view! {
+        <h1>"Saved games"</h1>
+        <Suspense fallback=move || {
+            view! { <div>"Loading..."</div> }
+        }>
+            <ul class="saved-games">{rows}</ul>
+        </Suspense>
+    }
 }
 
 #[component]
-fn Solitaire() -> impl IntoView {
-    let game =
-        create_resource(|| (), |_| async { fetch_cards().await.unwrap() });
+fn LoadedGame() -> impl IntoView {
+    let params = use_params_map();
+    let id = move || {
+        params.with(|params| params.get("id").cloned().unwrap_or_default())
+    };
+    let game = create_resource(id, load_game);
+
+    let navigate = use_navigate();
+    let new_deal =
+        Callback::new(move |_| navigate("/", Default::default()));
 
     view! {
         <Suspense fallback=move || {
             view! { <div>"Loading..."</div> }
         }>
-            {move || game.get().map(|game| view! { <Game game /> })}
+            {move || {
+                game.get()
+                    .map(|game| match game {
+                        Ok(saved_game) => {
+                            view! {
+                                <PersistedGame saved_game new_deal />
+                            }
+                        }
+                        Err(err) => {
+                            view! {
+                                <p>"Couldn't load game: " {err.to_string()}</p>
+                            }
+                                .into_view()
+                        }
+                    })
+            }}
         </Suspense>
     }
 }
+
+#[server(SaveGameRecords, "/api", "Cbor")]
+pub async fn save_game_records(
+    records: Vec<GameRecord>,
+) -> Result<(), ServerFnError> {
+    tokio::task::spawn_blocking(move || {
+        let conn = db::connect()?;
+        records
+            .iter()
+            .try_for_each(|record| db::record_game(&conn, record))
+    })
+    .await??;
+
+    Ok(())
+}
+
+#[server(FetchGameRecords, "/api", "Cbor")]
+pub async fn fetch_game_records() -> Result<Vec<GameRecord>, ServerFnError>
+{
+    let records = tokio::task::spawn_blocking(move || {
+        let conn = db::connect()?;
+        db::list_records(&conn)
+    })
+    .await??;
+
+    Ok(records)
+}
@@ -0,0 +1,212 @@
+use crate::storage;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell, collections::HashMap, fmt, str::FromStr, time::Duration,
+};
+use wasm_bindgen::JsCast;
+use web_sys::Element;
+
+// FLIP animations: after every change to the board, each card is measured
+// where it now sits, offset back to where it was last measured, and then
+// transitioned into place. Cards are matched up by the `data-card`
+// attribute face-up cards carry.
+
+const MOTION_KEY: &str = "solitaire:motion";
+const SLIDE: Duration = Duration::from_millis(250);
+const FADE: Duration = Duration::from_millis(150);
+// The stock is drawn as a single face-down card, so it is tracked by name
+const STOCK: &str = "stock";
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Motion {
+    #[default]
+    Full,
+    Reduced,
+    Off,
+}
+
+impl Motion {
+    // Falls back to the system's reduced motion preference
+    pub fn load() -> Self {
+        storage::load(MOTION_KEY).unwrap_or_else(|| {
+            let reduced = window()
+                .match_media("(prefers-reduced-motion: reduce)")
+                .ok()
+                .flatten()
+                .is_some_and(|query| query.matches());
+            if reduced {
+                Motion::Reduced
+            } else {
+                Motion::Full
+            }
+        })
+    }
+
+    pub fn save(self) {
+        _ = storage::save(MOTION_KEY, &self);
+    }
+}
+
+impl fmt::Display for Motion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Motion::Full => write!(f, "full"),
+            Motion::Reduced => write!(f, "reduced"),
+            Motion::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl FromStr for Motion {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Motion::Full),
+            "reduced" => Ok(Motion::Reduced),
+            "off" => Ok(Motion::Off),
+            _ => Err(()),
+        }
+    }
+}
+
+thread_local! {
+    static POSITIONS: RefCell<HashMap<String, (f64, f64)>> =
+        RefCell::new(HashMap::new());
+}
+
+fn position(element: &Element) -> (f64, f64) {
+    let rect = element.get_bounding_client_rect();
+    (rect.left(), rect.top())
+}
+
+fn cards() -> Vec<(String, Element)> {
+    let Ok(nodes) =
+        document().query_selector_all("[data-card], #deck > img")
+    else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.item(i)?.dyn_into::<Element>().ok())
+        .map(|card| {
+            let key = card
+                .get_attribute("data-card")
+                .unwrap_or(STOCK.to_owned());
+            (key, card)
+        })
+        .collect()
+}
+
+// Remember where every card is without animating anything
+pub fn measure() {
+    let positions = cards()
+        .into_iter()
+        .map(|(key, card)| (key, position(&card)))
+        .collect();
+    POSITIONS.with(|previous| *previous.borrow_mut() = positions);
+}
+
+// Call after the board changes; `drew` says whether cards left the stock
+pub fn animate(motion: Motion, drew: bool) {
+    request_animation_frame(move || {
+        let previous = POSITIONS.with(|previous| previous.take());
+        let element_position = |id: &str| {
+            document().get_element_by_id(id).map(|e| position(&e))
+        };
+        let (stock, waste) =
+            (element_position("deck"), element_position("waste"));
+
+        let mut next = HashMap::new();
+        for (key, card) in cards() {
+            let to = position(&card);
+            next.insert(key.clone(), to);
+            if motion == Motion::Off || previous.is_empty() {
+                continue;
+            }
+
+            let in_waste = card.closest(".waste").ok().flatten().is_some();
+            let in_pile = card.closest(".pile").ok().flatten().is_some();
+            let from = match previous.get(&key) {
+                Some(&from) => Some(from),
+                None if key == STOCK => waste,
+                None if in_waste && drew => stock,
+                None => None,
+            };
+            match from {
+                Some(from) if from != to => slide(&card, from, to, motion),
+                None if in_pile => flip(&card, motion),
+                _ => {}
+            }
+        }
+        POSITIONS.with(|positions| *positions.borrow_mut() = next);
+    });
+}
+
+// Reading the layout between the two styles makes the browser commit the
+// starting point, so the second style transitions from it
+fn transition(
+    card: &Element,
+    start: String,
+    end: String,
+    duration: Duration,
+) {
+    _ = card.set_attribute("style", &start);
+    _ = card.get_bounding_client_rect();
+    _ = card.set_attribute("style", &end);
+
+    let card = card.clone();
+    set_timeout(move || _ = card.remove_attribute("style"), duration);
+}
+
+fn slide(
+    card: &Element,
+    from: (f64, f64),
+    to: (f64, f64),
+    motion: Motion,
+) {
+    match motion {
+        Motion::Full => transition(
+            card,
+            format!(
+                "transform: translate({}px, {}px); transition: none",
+                from.0 - to.0,
+                from.1 - to.1
+            ),
+            format!(
+                "transform: none; transition: transform {}ms ease-out",
+                SLIDE.as_millis()
+            ),
+            SLIDE,
+        ),
+        Motion::Reduced => fade(card),
+        Motion::Off => {}
+    }
+}
+
+fn flip(card: &Element, motion: Motion) {
+    match motion {
+        Motion::Full => transition(
+            card,
+            "transform: rotateY(90deg); transition: none".to_owned(),
+            format!(
+                "transform: none; transition: transform {}ms ease-out",
+                SLIDE.as_millis()
+            ),
+            SLIDE,
+        ),
+        Motion::Reduced => fade(card),
+        Motion::Off => {}
+    }
+}
+
+fn fade(card: &Element) {
+    transition(
+        card,
+        "opacity: 0; transition: none".to_owned(),
+        format!("opacity: 1; transition: opacity {}ms", FADE.as_millis()),
+        FADE,
+    );
+}
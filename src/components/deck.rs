@@ -1,48 +1,177 @@
 use crate::{
-    components::game::Game,
-    game::{CardOutline, FaceDownCard, Selection, Solitaire},
+    components::{drag, touch},
+    game::{
+        labels::{cell_label, deck_label, foundation_label, waste_label},
+        CardOutline, FaceDownCard, Selection, Solitaire,
+    },
 };
-use leptos::ev::{DragEvent, MouseEvent};
+use leptos::ev::{DragEvent, MouseEvent, PointerEvent};
 use leptos::*;
 
 #[component]
 pub fn DeckArea() -> impl IntoView {
+    let rules = expect_context::<Solitaire>().rules();
+    if !rules.has_stock() {
+        return view! { <FreeCells /> }.into_view();
+    }
+
     view! {
         <div class="deck-area">
             <Deck />
-            <Waste />
+            {rules.has_waste().then(|| view! { <Waste /> })}
         </div>
     }
+    .into_view()
 }
 
 #[component]
-fn Waste() -> impl IntoView {
-    let mut game = expect_context::<Game>();
-    let waste = game.borrow().waste;
-    let waste =
-        move || waste().last().map(|card| card.view()).collect_view();
-
-    let click = {
-        let game = game.clone();
-        move |e: MouseEvent| {
-            e.stop_propagation();
-            game.borrow_mut().play(Selection::Waste);
+fn FreeCells() -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    let cells = move || {
+        game.cells()
+            .iter()
+            .enumerate()
+            .map(|(idx, _)| view! { <FreeCell idx /> })
+            .collect_view()
+    };
+
+    view! { <div class="cells">{cells}</div> }
+}
+
+#[component]
+fn FreeCell(idx: usize) -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    let cell = game.cells()[idx];
+    let label = move || cell.with(|cards| cell_label(idx, cards));
+    let card = move || cell().last().map(|card| card.view());
+    let hinted = move || game.is_hinted(Selection::Cell(idx));
+    let focused = move || game.is_focused(Selection::Cell(idx));
+    let drop_target = move || game.is_drop_target(Selection::Cell(idx));
+
+    let click = move |e: MouseEvent| {
+        e.stop_propagation();
+        _ = game.play(Selection::Cell(idx));
+    };
+    let send_to_foundation = move |e: MouseEvent| {
+        e.prevent_default();
+        e.stop_propagation();
+        if touch::is_dragging() {
+            return;
+        }
+        _ = game.send_to_foundation(Selection::Cell(idx));
+    };
+    let drag_start = move |e: DragEvent| {
+        if cell.with_untracked(Vec::is_empty) {
+            e.prevent_default();
+            return;
         }
+        let top = drag::event_element(&e)
+            .and_then(|cell| cell.last_element_child());
+        drag::start_drag(&e, game, Selection::Cell(idx), top);
+    };
+    let drag_end = move |_| drag::end_drag(game);
+    let pointer_down = move |e: PointerEvent| {
+        let top = drag::event_element(&e)
+            .and_then(|cell| cell.last_element_child())
+            .filter(|top| top.tag_name() == "IMG");
+        if let Some(top) = top {
+            touch::pointer_down(&e, game, Selection::Cell(idx), top, 1);
+        }
+    };
+    let drag_over =
+        move |e| drag::drag_over(&e, game, Selection::Cell(idx));
+    let drag_leave = move |_| drag::drag_leave(game, Selection::Cell(idx));
+    let drop = move |e| drag::drop(&e, game, Selection::Cell(idx));
+
+    view! {
+        <div
+            id=Selection::Cell(idx).dom_id()
+            role="button"
+            aria-label=label
+            class="cell"
+            class:selected=hinted
+            class:focused=focused
+            class:drop-target=drop_target
+            on:click=click
+            on:dblclick=send_to_foundation
+            on:contextmenu=send_to_foundation
+            draggable="true"
+            on:dragstart=drag_start
+            on:dragend=drag_end
+            on:pointerdown=pointer_down
+            on:dragover=drag_over
+            on:dragleave=drag_leave
+            on:drop=drop
+        >
+            <CardOutline />
+            {card}
+        </div>
+    }
+}
+
+#[component]
+fn Waste() -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    let waste = game.waste;
+    let fan = game.rules().draw.count();
+    let label = move || waste.with(|waste| waste_label(waste));
+    let hinted = move || game.is_hinted(Selection::Waste);
+    let focused = move || game.is_focused(Selection::Waste);
+    let waste = move || {
+        let waste = waste();
+        waste[waste.len().saturating_sub(fan)..]
+            .iter()
+            .map(|card| card.view())
+            .collect_view()
     };
 
-    let drag = {
-        let game = game.clone();
-        move |e: DragEvent| {
-            game.borrow_mut().play(Selection::Waste);
+    let click = move |e: MouseEvent| {
+        e.stop_propagation();
+        _ = game.play(Selection::Waste);
+    };
+
+    let drag_start = move |e: DragEvent| {
+        if game.waste.with_untracked(Vec::is_empty) {
+            e.prevent_default();
+            return;
         }
+        let top = drag::event_element(&e)
+            .and_then(|waste| waste.last_element_child());
+        drag::start_drag(&e, game, Selection::Waste, top);
     };
+    let drag_end = move |_| drag::end_drag(game);
+    let pointer_down = move |e: PointerEvent| {
+        let top = drag::event_element(&e)
+            .and_then(|waste| waste.last_element_child())
+            .filter(|top| top.tag_name() == "IMG");
+        if let Some(top) = top {
+            touch::pointer_down(&e, game, Selection::Waste, top, 1);
+        }
+    };
+    let send_to_foundation = move |e: MouseEvent| {
+        e.prevent_default();
+        e.stop_propagation();
+        if touch::is_dragging() {
+            return;
+        }
+        _ = game.send_to_foundation(Selection::Waste);
+    };
+
     view! {
         <div
-            class="deck"
+            id=Selection::Waste.dom_id()
+            role="button"
+            aria-label=label
+            class="deck waste"
+            class:selected=hinted
+            class:focused=focused
             on:click=click
-            on:dragstart=drag.clone()
-            on:drop=drag
-            on:dragover=move |e| e.prevent_default()
+            on:dblclick=send_to_foundation
+            on:contextmenu=send_to_foundation
+            draggable="true"
+            on:dragstart=drag_start
+            on:dragend=drag_end
+            on:pointerdown=pointer_down
         >
             <CardOutline />
             {waste}
@@ -52,11 +181,15 @@ fn Waste() -> impl IntoView {
 
 #[component]
 fn Deck() -> impl IntoView {
-    let game = expect_context::<Game>();
-    let deck = game.borrow().deck;
+    let game = expect_context::<Solitaire>();
+    let deck = game.deck;
+    let label = move || deck.with(|deck| deck_label(deck));
+    let exhausted = move || game.is_stock_exhausted();
+    let hinted = move || game.is_hinted(Selection::Deck);
+    let focused = move || game.is_focused(Selection::Deck);
 
     let click = move |_| {
-        game.borrow_mut().draw();
+        _ = game.draw();
     };
 
     let deck = move || {
@@ -67,7 +200,16 @@ fn Deck() -> impl IntoView {
     };
 
     view! {
-        <div class="deck" on:click=click>
+        <div
+            id=Selection::Deck.dom_id()
+            role="button"
+            aria-label=label
+            class="deck"
+            class:exhausted=exhausted
+            class:selected=hinted
+            class:focused=focused
+            on:click=click
+        >
             <CardOutline />
             {deck}
         </div>
@@ -76,10 +218,9 @@ fn Deck() -> impl IntoView {
 
 #[component]
 pub fn Foundations() -> impl IntoView {
-    let game = expect_context::<Game>();
+    let game = expect_context::<Solitaire>();
     let foundations = move || {
-        game.borrow()
-            .foundations
+        game.foundations()
             .iter()
             .enumerate()
             .map(|(idx, _)| view! { <Foundation idx /> })
@@ -91,34 +232,67 @@ pub fn Foundations() -> impl IntoView {
 
 #[component]
 fn Foundation(idx: usize) -> impl IntoView {
-    let mut game = expect_context::<Game>();
+    let game = expect_context::<Solitaire>();
+    let label = {
+        let foundation = game.foundations()[idx];
+        move || foundation.with(|cards| foundation_label(idx, cards))
+    };
     let foundation = {
-        let foundation = game.borrow().foundations[idx];
+        let foundation = game.foundations()[idx];
         move || foundation().last().map(|card| card.view())
     };
+    let hinted = move || game.is_hinted(Selection::Foundation(idx));
+    let focused = move || game.is_focused(Selection::Foundation(idx));
 
-    let click = {
-        let game = game.clone();
-        move |e: MouseEvent| {
-            e.stop_propagation();
-            game.borrow_mut().play(Selection::Foundation(idx));
+    let click = move |e: MouseEvent| {
+        e.stop_propagation();
+        _ = game.play(Selection::Foundation(idx));
+    };
+    let drop_target =
+        move || game.is_drop_target(Selection::Foundation(idx));
+    let drag_start = move |e: DragEvent| {
+        let foundation = game.foundations()[idx];
+        if foundation.with_untracked(Vec::is_empty) {
+            e.prevent_default();
+            return;
         }
+        let top = drag::event_element(&e)
+            .and_then(|foundation| foundation.last_element_child());
+        drag::start_drag(&e, game, Selection::Foundation(idx), top);
     };
-    let drag = {
-        let game = game.clone();
-        move |e: DragEvent| {
-            let mut game = game.borrow_mut();
-            game.play(Selection::Foundation(idx));
+    let drag_end = move |_| drag::end_drag(game);
+    let pointer_down = move |e: PointerEvent| {
+        let top = drag::event_element(&e)
+            .and_then(|foundation| foundation.last_element_child())
+            .filter(|top| top.tag_name() == "IMG");
+        if let Some(top) = top {
+            let s = Selection::Foundation(idx);
+            touch::pointer_down(&e, game, s, top, 1);
         }
     };
+    let drag_over =
+        move |e| drag::drag_over(&e, game, Selection::Foundation(idx));
+    let drag_leave =
+        move |_| drag::drag_leave(game, Selection::Foundation(idx));
+    let drop = move |e| drag::drop(&e, game, Selection::Foundation(idx));
 
     view! {
         <div
+            id=Selection::Foundation(idx).dom_id()
+            role="button"
+            aria-label=label
             class="foundation"
+            class:selected=hinted
+            class:focused=focused
+            class:drop-target=drop_target
             on:click=click
-            on:dragstart=drag.clone()
-            on:drop=drag
-            on:dragover=move |e| e.prevent_default()
+            draggable="true"
+            on:dragstart=drag_start
+            on:dragend=drag_end
+            on:pointerdown=pointer_down
+            on:dragover=drag_over
+            on:dragleave=drag_leave
+            on:drop=drop
         >
             <CardOutline />
             {foundation}
@@ -0,0 +1,113 @@
+use crate::{
+    components::touch,
+    game::{Selection, Solitaire},
+};
+use leptos::{ev::DragEvent, *};
+use std::time::Duration;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event};
+
+// Shared drag-and-drop plumbing. The dragged selection lives on the game;
+// the DataTransfer payload is only there because some browsers won't start
+// a drag without one. Drop targets only accept the drag (by cancelling
+// `dragover`) when the move would be legal. Anything else is left to the
+// browser, which animates the card back where it came from.
+
+const PAYLOAD: &str = "text/plain";
+
+pub fn start_drag(
+    e: &DragEvent,
+    game: Solitaire,
+    s: Selection,
+    image: Option<Element>,
+) {
+    // Touch drags are handled by the pointer layer instead
+    if touch::is_dragging() {
+        e.prevent_default();
+        return;
+    }
+    let Some(transfer) = e.data_transfer() else {
+        return;
+    };
+    _ = transfer.set_data(PAYLOAD, &s.dom_id());
+    transfer.set_effect_allowed("move");
+    if let Some(image) = image {
+        transfer.set_drag_image(&image, e.offset_x(), e.offset_y());
+    }
+    game.start_drag(s);
+}
+
+pub fn end_drag(game: Solitaire) {
+    game.end_drag();
+}
+
+pub fn drag_over(e: &DragEvent, game: Solitaire, to: Selection) {
+    if !game.can_drop(to) {
+        return;
+    }
+    e.prevent_default();
+    if let Some(transfer) = e.data_transfer() {
+        transfer.set_drop_effect("move");
+    }
+    if game.drop_target.get_untracked() != Some(to) {
+        game.drop_target.set(Some(to));
+    }
+}
+
+pub fn drag_leave(game: Solitaire, to: Selection) {
+    if game.drop_target.get_untracked() == Some(to) {
+        game.drop_target.set(None);
+    }
+}
+
+// Only drags that started on this board count: text dragged in from
+// elsewhere can look just like a payload
+pub fn drop(e: &DragEvent, game: Solitaire, to: Selection) {
+    e.prevent_default();
+    e.stop_propagation();
+    if let Some(from) = game.dragging.get_untracked() {
+        _ = game.drop(from, to);
+    }
+}
+
+// Copies a card and the `n - 1` cards stacked on top of it into a new
+// element on the page, for use as a drag image or a touch drag ghost
+pub fn copy_stack(
+    card: &Element,
+    n: usize,
+    class: &str,
+) -> Option<Element> {
+    let document = document();
+    let stack = document.create_element("div").ok()?;
+    stack.set_class_name(class);
+    _ = stack.set_attribute(
+        "style",
+        &format!("width: {}px", card.client_width()),
+    );
+
+    let mut next = Some(card.clone());
+    for _ in 0..n {
+        let card = next?;
+        let copy = card.clone_node_with_deep(true).ok()?;
+        let copy = copy.dyn_into::<Element>().ok()?;
+        copy.remove_attribute("id").ok()?;
+        stack.append_child(&copy).ok()?;
+        next = card.next_element_sibling();
+    }
+
+    document.body()?.append_child(&stack).ok()?;
+    Some(stack)
+}
+
+// The browser only snapshots the element being dragged, so copy the whole
+// stack into an off-screen element to use as the drag image
+pub fn stack_preview(card: &Element, n: usize) -> Option<Element> {
+    let preview = copy_stack(card, n, "drag-preview")?;
+    let cleanup = preview.clone();
+    set_timeout(move || cleanup.remove(), Duration::ZERO);
+    Some(preview)
+}
+
+pub fn event_element(e: &Event) -> Option<Element> {
+    e.current_target()?.dyn_into::<Element>().ok()
+}
@@ -1,33 +1,444 @@
 use crate::{
-    components::{DeckArea, Foundations, Pile},
-    game::{Selection, Solitaire},
+    app::save_game,
+    components::{
+        animate::{self, Motion},
+        touch, DeckArea, Foundations, Pile,
+    },
+    game::{
+        Direction, DrawMode, PassLimit, Rules, Scoring, Selection,
+        Solitaire, SpiderSuits, VariantKind,
+    },
 };
-use leptos::ev::{DragEvent, MouseEvent};
+use leptos::ev::{DragEvent, KeyboardEvent, MouseEvent};
 use leptos::*;
 use leptos_dom::log;
-use std::{cell::RefCell, rc::Rc};
+use leptos_router::{use_query_map, Form};
+use std::time::Duration;
+use wasm_bindgen::JsCast;
+use web_sys::Element;
 
-pub type Game = Rc<RefCell<Solitaire>>;
+const BOARD_LABEL: &str =
+    "Solitaire board. Arrow keys move, Enter plays, \
+    D draws, F sends to a foundation, Escape cancels.";
 
 #[component]
-pub fn Game(game: Solitaire) -> impl IntoView {
-    let game = Rc::new(RefCell::new(game));
-    provide_context(game.clone());
+pub fn Game(
+    game: Solitaire,
+    winnable: bool,
+    new_deal: Callback<()>,
+) -> impl IntoView {
+    let error = game.error;
+    let announcement = game.announcement;
+    let is_won = game.is_won;
+    let focus = game.focus;
+    provide_context(game);
 
-    let clear_selection = {
-        let game = game.clone();
-        move |e: MouseEvent| {
-            e.stop_propagation();
-            log!("Clearing selection");
-            game.borrow_mut().clear_selection();
-        }
+    let clear_selection = move |e: MouseEvent| {
+        e.stop_propagation();
+        log!("Clearing selection");
+        game.clear_selection();
     };
+
+    let shortcuts =
+        window_event_listener(ev::keydown, move |e: KeyboardEvent| {
+            let key = e.key();
+            if is_form_control(&e, matches!(key.as_str(), "Enter" | " ")) {
+                return;
+            }
+
+            if e.ctrl_key() || e.meta_key() {
+                match (key.to_lowercase().as_str(), e.shift_key()) {
+                    ("z", false) => game.undo(),
+                    ("z", true) | ("y", _) => game.redo(),
+                    _ => return,
+                }
+            } else {
+                match key.as_str() {
+                    "ArrowUp" => game.move_focus(Direction::Up),
+                    "ArrowDown" => game.move_focus(Direction::Down),
+                    "ArrowLeft" => game.move_focus(Direction::Left),
+                    "ArrowRight" => game.move_focus(Direction::Right),
+                    "Enter" | " " => _ = game.play_focused(),
+                    "d" | "D" => _ = game.draw(),
+                    "f" | "F" => _ = game.send_focused_to_foundation(),
+                    "Escape" => game.clear_selection(),
+                    _ => return,
+                }
+            }
+            e.prevent_default();
+        });
+    on_cleanup(move || shortcuts.remove());
+
+    let motion = create_rw_signal(Motion::default());
+    provide_context(motion);
+    create_effect(move |_| motion.set(Motion::load()));
+    create_effect(move |previous_stock: Option<usize>| {
+        let stock = game.deck.with(Vec::len);
+        game.waste.track();
+        game.piles().iter().for_each(|pile| pile.track());
+        game.foundations()
+            .iter()
+            .for_each(|foundation| foundation.track());
+        game.cells().iter().for_each(|cell| cell.track());
+
+        match previous_stock {
+            Some(previous) => {
+                animate::animate(motion.get_untracked(), stock < previous)
+            }
+            None => request_animation_frame(animate::measure),
+        }
+        stock
+    });
+    let resize = window_event_listener(ev::resize, |_| animate::measure());
+    on_cleanup(move || resize.remove());
+
+    let touch_listeners = [
+        window_event_listener(ev::pointermove, move |e| {
+            touch::pointer_move(&e, game)
+        }),
+        window_event_listener(ev::pointerup, move |e| {
+            touch::pointer_up(&e, game)
+        }),
+        window_event_listener(ev::pointercancel, move |_| {
+            touch::pointer_cancel(game)
+        }),
+        window_event_listener(ev::touchend, |e| touch::touch_end(&e)),
+    ];
+    on_cleanup(move || {
+        touch_listeners.into_iter().for_each(|l| l.remove())
+    });
+
+    create_effect(move |_| {
+        let clock = set_interval_with_handle(
+            move || game.tick(),
+            Duration::from_secs(1),
+        );
+        on_cleanup(move || {
+            if let Ok(clock) = clock {
+                clock.clear();
+            }
+        });
+    });
+
     view! {
         <h1>"Solitaire"</h1>
-        <div class="game" on:click=clear_selection>
+        <p class="move-error" role="alert">
+            {move || error().map(|err| err.to_string())}
+        </p>
+        <p class="sr-only" role="status" aria-live="polite">
+            {announcement}
+        </p>
+        <Controls winnable />
+        <Score />
+        <div
+            class="game"
+            role="application"
+            aria-label=BOARD_LABEL
+            aria-activedescendant=move || focus().map(|s| s.dom_id())
+            tabindex="0"
+            on:click=clear_selection
+        >
             <TopRow />
             <Piles />
         </div>
+        <Show when=is_won>
+            <Victory game new_deal />
+        </Show>
+    }
+}
+
+// Keys typed into the new game form (or used to press a button) belong to
+// that control, not the board
+fn is_form_control(e: &KeyboardEvent, include_buttons: bool) -> bool {
+    let Some(target) = e
+        .target()
+        .and_then(|target| target.dyn_into::<Element>().ok())
+    else {
+        return false;
+    };
+    match target.tag_name().as_str() {
+        "INPUT" | "SELECT" | "TEXTAREA" => true,
+        "BUTTON" | "A" => include_buttons,
+        _ => false,
+    }
+}
+
+pub fn format_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[component]
+fn Victory(game: Solitaire, new_deal: Callback<()>) -> impl IntoView {
+    let scoring = game.rules().scoring;
+
+    view! {
+        <div class="victory" role="dialog" aria-label="You won">
+            <div class="victory-summary">
+                <h2>"You won!"</h2>
+                <p>"Time: " {move || format_time(game.elapsed())}</p>
+                <p>"Moves: " {move || game.moves()}</p>
+                <p>"Score: " {move || scoring.format(game.score())}</p>
+                <button on:click=move |_| new_deal(())>"New deal"</button>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn Controls(winnable: bool) -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    let undo = move |_| game.undo();
+    let redo = move |_| game.redo();
+    let cant_undo = move || !game.can_undo();
+    let cant_redo = move || !game.can_redo();
+    let hint = move |e: MouseEvent| {
+        e.stop_propagation();
+        game.show_hint();
+    };
+    let seed = game.seed();
+    let rules = game.rules();
+
+    view! {
+        <div class="controls">
+            <button on:click=undo disabled=cant_undo title="Ctrl+Z">
+                "Undo"
+            </button>
+            <button on:click=redo disabled=cant_redo title="Ctrl+Y">
+                "Redo"
+            </button>
+            <a
+                href=format!("/game/{seed}?{}", rules.query())
+                title="Share this deal"
+            >
+                "Deal #"
+                {seed}
+            </a>
+            {winnable
+                .then(|| {
+                    view! {
+                        <span class="winnable" title="The solver beat this deal">
+                            "Winnable"
+                        </span>
+                    }
+                })}
+            <button on:click=hint>"Hint"</button>
+            <AutoComplete />
+            <SaveButton winnable />
+            <MotionSetting />
+            <NewGame rules />
+        </div>
+    }
+}
+
+#[component]
+fn SaveButton(winnable: bool) -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    // Saving again updates this session's save, but a game opened from a
+    // shared link is saved as a copy so the link still shows the original
+    let saved_id = create_rw_signal(None::<String>);
+    let save = create_action(move |_: &()| {
+        save_game(saved_id.get_untracked(), game.snapshot(winnable))
+    });
+    create_effect(move |_| {
+        if let Some(Ok(id)) = save.value().get() {
+            saved_id.set(Some(id));
+        }
+    });
+
+    let status = move || match save.value().get() {
+        Some(Err(_)) => Some("Save failed".into_view()),
+        _ => saved_id().map(|id| {
+            let href = format!("/saved/{id}");
+            view! { <a href=href>"Saved as " {id}</a> }.into_view()
+        }),
+    };
+
+    view! {
+        <button
+            on:click=move |e: MouseEvent| {
+                e.stop_propagation();
+                save.dispatch(());
+            }
+            disabled=save.pending()
+        >
+            "Save"
+        </button>
+        {status}
+    }
+}
+
+#[component]
+fn MotionSetting() -> impl IntoView {
+    let motion = expect_context::<RwSignal<Motion>>();
+    let change = move |e| {
+        if let Ok(m) = event_target_value(&e).parse::<Motion>() {
+            m.save();
+            motion.set(m);
+        }
+    };
+    let option = move |value: Motion, label: &'static str| {
+        view! {
+            <option value=value.to_string() selected=move || motion() == value>
+                {label}
+            </option>
+        }
+    };
+
+    view! {
+        <select aria-label="Animations" on:change=change>
+            {option(Motion::Full, "Full motion")}
+            {option(Motion::Reduced, "Reduced motion")}
+            {option(Motion::Off, "No animations")}
+        </select>
+    }
+}
+
+#[component]
+fn AutoComplete() -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+
+    view! {
+        <Show when=game.can_auto_complete>
+            <button on:click=move |_| game.auto_complete()>
+                "Auto-complete"
+            </button>
+        </Show>
+    }
+}
+
+#[component]
+fn NewGame(rules: Rules) -> impl IntoView {
+    // The deal doesn't otherwise change when the options stay the same
+    let query = use_query_map();
+    let next_deal = move || {
+        query.with(|query| {
+            let deal = query.get("deal").and_then(|d| d.parse().ok());
+            deal.map_or(1, |deal: u32| deal.wrapping_add(1)).to_string()
+        })
+    };
+    let variant = create_rw_signal(rules.variant.to_string());
+    let options = move || match variant().as_str() {
+        "spider" => view! { <SpiderOptions rules /> },
+        "freecell" => ().into_view(),
+        _ => view! { <KlondikeOptions rules /> },
+    };
+
+    view! {
+        <Form method="GET" action="/">
+            <select
+                name="variant"
+                on:change=move |e| variant.set(event_target_value(&e))
+            >
+                <option
+                    value="klondike"
+                    selected=rules.variant == VariantKind::Klondike
+                >
+                    "Klondike"
+                </option>
+                <option
+                    value="spider"
+                    selected=matches!(rules.variant, VariantKind::Spider(_))
+                >
+                    "Spider"
+                </option>
+                <option
+                    value="freecell"
+                    selected=rules.variant == VariantKind::FreeCell
+                >
+                    "FreeCell"
+                </option>
+            </select>
+            {options}
+            <input type="hidden" name="deal" value=next_deal />
+            <button type="submit">"New game"</button>
+        </Form>
+    }
+}
+
+#[component]
+fn KlondikeOptions(rules: Rules) -> impl IntoView {
+    let query = use_query_map();
+    let winnable_only = query.with_untracked(|query| {
+        query.get("winnable").is_some_and(|w| w == "true")
+    });
+
+    view! {
+        <select name="draw">
+            <option value="1" selected=rules.draw == DrawMode::One>
+                "Draw 1"
+            </option>
+            <option value="3" selected=rules.draw == DrawMode::Three>
+                "Draw 3"
+            </option>
+        </select>
+        <select name="passes">
+            <option
+                value="unlimited"
+                selected=rules.passes == PassLimit::Unlimited
+            >
+                "Unlimited passes"
+            </option>
+            <option value="3" selected=rules.passes == PassLimit::Three>
+                "3 passes"
+            </option>
+            <option value="1" selected=rules.passes == PassLimit::One>
+                "1 pass"
+            </option>
+        </select>
+        <select name="scoring">
+            <option value="standard" selected=rules.scoring == Scoring::Standard>
+                "Standard scoring"
+            </option>
+            <option value="vegas" selected=rules.scoring == Scoring::Vegas>
+                "Vegas scoring"
+            </option>
+        </select>
+        <label>
+            <input
+                type="checkbox"
+                name="winnable"
+                value="true"
+                checked=winnable_only
+            />
+            "Winnable deals only"
+        </label>
+    }
+}
+
+#[component]
+fn SpiderOptions(rules: Rules) -> impl IntoView {
+    let suits = match rules.variant {
+        VariantKind::Spider(suits) => suits,
+        _ => SpiderSuits::default(),
+    };
+
+    view! {
+        <select name="suits">
+            <option value="1" selected=suits == SpiderSuits::One>
+                "1 suit"
+            </option>
+            <option value="2" selected=suits == SpiderSuits::Two>
+                "2 suits"
+            </option>
+            <option value="4" selected=suits == SpiderSuits::Four>
+                "4 suits"
+            </option>
+        </select>
+    }
+}
+
+#[component]
+fn Score() -> impl IntoView {
+    let game = expect_context::<Solitaire>();
+    let scoring = game.rules().scoring;
+    let score = move || scoring.format(game.score());
+    let time = move || format_time(game.elapsed());
+
+    view! {
+        <p class="score">
+            "Score: " {score} " · Moves: " {move || game.moves()} " · Time: "
+            {time}
+        </p>
     }
 }
 
@@ -43,9 +454,9 @@ fn TopRow() -> impl IntoView {
 
 #[component]
 fn Piles() -> impl IntoView {
-    let game = expect_context::<Game>();
+    let game = expect_context::<Solitaire>();
     let piles = {
-        let piles = game.borrow().piles;
+        let piles = game.piles();
         move || {
             piles
                 .iter()
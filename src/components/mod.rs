@@ -1,7 +1,15 @@
+mod animate;
+
 mod deck;
 use deck::{DeckArea, Foundations};
 
+mod drag;
+
 mod pile;
 use pile::Pile;
 
 pub mod game;
+
+pub mod stats;
+
+mod touch;
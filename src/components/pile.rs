@@ -1,21 +1,17 @@
 use crate::{
-    components::game::Game,
-    game::{Card, CardOutline, Selection, Solitaire},
+    components::{drag, touch},
+    game::{labels::pile_label, Card, CardOutline, Selection, Solitaire},
 };
-use leptos::ev::{DragEvent, MouseEvent};
+use leptos::ev::{DragEvent, MouseEvent, PointerEvent};
 use leptos::*;
 use leptos_dom::log;
 
 #[component]
-pub fn Pile(idx: usize, cards: RwSignal<Vec<Card>>) -> impl IntoView {
-    let game = expect_context::<Game>();
+pub fn Pile(idx: usize, cards: Memo<Vec<Card>>) -> impl IntoView {
+    let game = expect_context::<Solitaire>();
 
+    let label = move || cards.with(|cards| pile_label(idx, cards));
     let pile = move || {
-        cards.update(|cards| {
-            if let Some(card) = cards.last_mut() {
-                card.flip();
-            }
-        });
         let cards = cards();
         (1..=cards.len()).rev().zip(cards.into_iter())
     };
@@ -23,17 +19,31 @@ pub fn Pile(idx: usize, cards: RwSignal<Vec<Card>>) -> impl IntoView {
         view! { <PileCard pile_idx=idx card_idx card=card /> }
     };
 
-    let click = move || {
-        game.borrow_mut().play(Selection::Pile(idx, 0));
+    let hinted = move || game.is_hinted(Selection::Pile(idx, 0));
+    let focused = move || game.is_focused(Selection::Pile(idx, 0));
+    let drop_target = move || game.is_drop_target(Selection::Pile(idx, 0));
+    let click = move |_| {
+        _ = game.play(Selection::Pile(idx, 0));
     };
-    let drag = click.clone();
+    let drag_over =
+        move |e| drag::drag_over(&e, game, Selection::Pile(idx, 0));
+    let drag_leave =
+        move |_| drag::drag_leave(game, Selection::Pile(idx, 0));
+    let drop = move |e| drag::drop(&e, game, Selection::Pile(idx, 0));
 
     view! {
         <div
+            id=Selection::Pile(idx, 0).dom_id()
+            role="group"
+            aria-label=label
             class="pile"
-            on:click=move |_| click()
-            on:drop=move |_| drag()
-            on:dragover=move |e| e.prevent_default()
+            class:selected=hinted
+            class:focused=focused
+            class:drop-target=drop_target
+            on:click=click
+            on:dragover=drag_over
+            on:dragleave=drag_leave
+            on:drop=drop
         >
             <CardOutline />
             <For
@@ -51,29 +61,60 @@ fn PileCard(
     card_idx: usize,
     card: Card,
 ) -> impl IntoView {
-    let game = expect_context::<Game>();
-    let click = {
-        let game = game.clone();
-        move |e: MouseEvent| {
-            e.stop_propagation();
-            game.borrow_mut().play(Selection::Pile(pile_idx, card_idx))
+    let game = expect_context::<Solitaire>();
+    let hinted =
+        move || game.is_hinted(Selection::Pile(pile_idx, card_idx));
+    let focused =
+        move || game.is_focused(Selection::Pile(pile_idx, card_idx));
+    let click = move |e: MouseEvent| {
+        e.stop_propagation();
+        _ = game.play(Selection::Pile(pile_idx, card_idx));
+    };
+    let dragged =
+        move || game.is_dragged(Selection::Pile(pile_idx, card_idx));
+    let faceup = card.is_faceup();
+    let drag_start = move |e: DragEvent| {
+        if !faceup {
+            e.prevent_default();
+            return;
+        }
+        let preview = drag::event_element(&e)
+            .and_then(|card| drag::stack_preview(&card, card_idx));
+        let s = Selection::Pile(pile_idx, card_idx);
+        drag::start_drag(&e, game, s, preview);
+    };
+    let drag_end = move |_| drag::end_drag(game);
+    let pointer_down = move |e: PointerEvent| {
+        if let (true, Some(card)) = (faceup, drag::event_element(&e)) {
+            let s = Selection::Pile(pile_idx, card_idx);
+            touch::pointer_down(&e, game, s, card, card_idx);
         }
     };
-    let drag = {
-        let game = game.clone();
-        move |e: DragEvent| {
-            let mut game = game.borrow_mut();
-            game.play(Selection::Pile(pile_idx, card_idx))
+    let send_to_foundation = move |e: MouseEvent| {
+        e.prevent_default();
+        e.stop_propagation();
+        if touch::is_dragging() {
+            return;
         }
+        _ = game.send_to_foundation(Selection::Pile(pile_idx, card_idx));
     };
 
     view! {
         <span
+            id=Selection::Pile(pile_idx, card_idx).dom_id()
+            role="button"
+            aria-label=card.label()
             class="card"
+            class:selected=hinted
+            class:focused=focused
+            class:dragging=dragged
             on:click=click
-            on:dragstart=drag.clone()
-            on:drop=drag
-            on:dragover=move |e| e.prevent_default()
+            on:dblclick=send_to_foundation
+            on:contextmenu=send_to_foundation
+            draggable=faceup.to_string()
+            on:dragstart=drag_start
+            on:dragend=drag_end
+            on:pointerdown=pointer_down
         >
             {card.view()}
         </span>
@@ -0,0 +1,158 @@
+use crate::{
+    app::{fetch_game_records, save_game_records},
+    components::game::format_time,
+    game::{GameRecord, Stats},
+    storage,
+};
+use leptos::*;
+
+const RECORDS_KEY: &str = "solitaire:game-records";
+const SYNC_KEY: &str = "solitaire:stats-sync";
+// How many of the local records the server already has
+const SYNCED_KEY: &str = "solitaire:stats-synced";
+
+fn load_records() -> Vec<GameRecord> {
+    storage::load(RECORDS_KEY).unwrap_or_default()
+}
+
+fn is_sync_enabled() -> bool {
+    storage::load(SYNC_KEY).unwrap_or(false)
+}
+
+pub fn record_game(record: GameRecord) {
+    let mut records = load_records();
+    records.push(record);
+    _ = storage::save(RECORDS_KEY, &records);
+    sync_records();
+}
+
+fn sync_records() {
+    if !is_sync_enabled() {
+        return;
+    }
+    let records = load_records();
+    let synced = storage::load::<usize>(SYNCED_KEY)
+        .unwrap_or(0)
+        .min(records.len());
+    if synced == records.len() {
+        return;
+    }
+
+    let total = records.len();
+    let unsynced = records[synced..].to_vec();
+    spawn_local(async move {
+        if save_game_records(unsynced).await.is_ok() {
+            _ = storage::save(SYNCED_KEY, &total);
+        }
+    });
+}
+
+#[component]
+pub fn StatsPage() -> impl IntoView {
+    let records = create_rw_signal(Vec::new());
+    let sync = create_rw_signal(false);
+    create_effect(move |_| {
+        records.set(load_records());
+        sync.set(is_sync_enabled());
+    });
+
+    let server_records = create_resource(sync, |sync| async move {
+        if !sync {
+            return None;
+        }
+        Some(fetch_game_records().await)
+    });
+
+    let toggle_sync = move |_| {
+        let enabled = !sync.get_untracked();
+        _ = storage::save(SYNC_KEY, &enabled);
+        sync.set(enabled);
+        sync_records();
+    };
+
+    let server_stats = move || {
+        server_records.get().flatten().map(|records| match records {
+            Ok(records) => view! {
+                <h2>"All devices"</h2>
+                <StatsSummary stats=Stats::new(&records) />
+            }
+            .into_view(),
+            Err(err) => view! {
+                <p>"Couldn't load server statistics: " {err.to_string()}</p>
+            }
+            .into_view(),
+        })
+    };
+
+    view! {
+        <h1>"Statistics"</h1>
+        {move || {
+            let stats = records.with(|records| Stats::new(records));
+            view! { <StatsSummary stats /> }
+        }}
+        <label class="stats-sync">
+            <input type="checkbox" prop:checked=sync on:change=toggle_sync />
+            "Keep statistics on the server"
+        </label>
+        <Suspense fallback=move || {
+            view! { <div>"Loading..."</div> }
+        }>{server_stats}</Suspense>
+    }
+}
+
+#[component]
+fn StatsSummary(stats: Stats) -> impl IntoView {
+    if stats.played == 0 {
+        return view! { <p>"No games played yet."</p> }.into_view();
+    }
+
+    let modes = stats
+        .modes
+        .iter()
+        .map(|mode| {
+            let scoring = mode.rules.scoring;
+            view! {
+                <tr>
+                    <td>{mode.rules.to_string()}</td>
+                    <td>{mode.played}</td>
+                    <td>{mode.won}</td>
+                    <td>
+                        {mode.best_time.map(format_time).unwrap_or("–".into())}
+                    </td>
+                    <td>
+                        {mode
+                            .best_score
+                            .map(|score| scoring.format(score))
+                            .unwrap_or("–".into())}
+                    </td>
+                </tr>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="stats">
+            <p>
+                "Played: " {stats.played} " · Won: " {stats.won} " · Win rate: "
+                {format!("{:.0}%", stats.win_rate() * 100.0)}
+            </p>
+            <p>
+                "Current streak: " {stats.current_streak} " · Longest streak: "
+                {stats.longest_streak}
+            </p>
+            <table>
+                <thead>
+                    <tr>
+                        <th>"Mode"</th>
+                        <th>"Played"</th>
+                        <th>"Won"</th>
+                        <th>"Best time"</th>
+                        <th>"Best score"</th>
+                    </tr>
+                </thead>
+                <tbody>{modes}</tbody>
+            </table>
+        </div>
+    }
+    .into_view()
+}
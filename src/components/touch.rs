@@ -0,0 +1,221 @@
+use crate::{
+    components::drag,
+    game::{Selection, Solitaire},
+};
+use leptos::{
+    ev::{PointerEvent, TouchEvent},
+    *,
+};
+use leptos_dom::helpers::TimeoutHandle;
+use std::{cell::RefCell, time::Duration};
+use web_sys::Element;
+
+// Touch screens don't fire HTML5 drag events, so fingers get their own
+// drag: press and hold a card to pick it (and everything on it) up, then
+// lift the finger over the destination. Quick taps fall through to the
+// usual click handlers for tap-to-select, tap-to-place.
+
+const LONG_PRESS: Duration = Duration::from_millis(350);
+const SNAP_BACK: Duration = Duration::from_millis(200);
+// How far a finger can wander before a press becomes a scroll
+const SLOP: f64 = 10.0;
+
+struct Press {
+    pointer: i32,
+    source: Selection,
+    stack: Element,
+    n: usize,
+    start: (f64, f64),
+    timer: Option<TimeoutHandle>,
+    drag: Option<TouchDrag>,
+}
+
+struct TouchDrag {
+    ghost: Element,
+    offset: (f64, f64),
+    origin: (f64, f64),
+}
+
+thread_local! {
+    static PRESS: RefCell<Option<Press>> = const { RefCell::new(None) };
+    static SUPPRESS_CLICK: RefCell<bool> = const { RefCell::new(false) };
+}
+
+pub fn is_dragging() -> bool {
+    PRESS.with(|press| {
+        press
+            .borrow()
+            .as_ref()
+            .is_some_and(|press| press.drag.is_some())
+    })
+}
+
+fn position(e: &PointerEvent) -> (f64, f64) {
+    (f64::from(e.client_x()), f64::from(e.client_y()))
+}
+
+// `stack` is the lowest card being picked up and `n` the number of cards
+// in the stack
+pub fn pointer_down(
+    e: &PointerEvent,
+    game: Solitaire,
+    source: Selection,
+    stack: Element,
+    n: usize,
+) {
+    if e.pointer_type() != "touch" || !e.is_primary() {
+        return;
+    }
+
+    let timer =
+        set_timeout_with_handle(move || start_drag(game), LONG_PRESS).ok();
+    cancel();
+    PRESS.with(|press| {
+        *press.borrow_mut() = Some(Press {
+            pointer: e.pointer_id(),
+            source,
+            stack,
+            n,
+            start: position(e),
+            timer,
+            drag: None,
+        })
+    });
+}
+
+fn start_drag(game: Solitaire) {
+    PRESS.with(|press| {
+        let mut press = press.borrow_mut();
+        let Some(press) = press.as_mut() else {
+            return;
+        };
+        let Some(ghost) =
+            drag::copy_stack(&press.stack, press.n, "touch-ghost")
+        else {
+            return;
+        };
+
+        let rect = press.stack.get_bounding_client_rect();
+        let origin = (rect.left(), rect.top());
+        let offset = (press.start.0 - origin.0, press.start.1 - origin.1);
+        place(&ghost, origin, rect.width());
+        press.timer = None;
+        press.drag = Some(TouchDrag {
+            ghost,
+            offset,
+            origin,
+        });
+        game.start_drag(press.source);
+    });
+}
+
+fn place(ghost: &Element, (left, top): (f64, f64), width: f64) {
+    _ = ghost.set_attribute(
+        "style",
+        &format!("left: {left}px; top: {top}px; width: {width}px"),
+    );
+}
+
+// The drop target under the finger; the ghost ignores pointer events so it
+// never hides what's beneath it
+fn target_at((x, y): (f64, f64)) -> Option<Selection> {
+    let element = document().element_from_point(x as f32, y as f32)?;
+    let target = element.closest(".pile, .foundation, .cell").ok()??;
+    Selection::from_dom_id(&target.id())
+}
+
+pub fn pointer_move(e: &PointerEvent, game: Solitaire) {
+    let position = position(e);
+    let moved = PRESS.with(|press| {
+        let mut press = press.borrow_mut();
+        let press = press.as_mut()?;
+        if press.pointer != e.pointer_id() {
+            return None;
+        }
+        let Some(drag) = &press.drag else {
+            let (dx, dy) =
+                (position.0 - press.start.0, position.1 - press.start.1);
+            return Some(dx.hypot(dy) > SLOP);
+        };
+
+        let width = press.stack.client_width().into();
+        let left = position.0 - drag.offset.0;
+        let top = position.1 - drag.offset.1;
+        place(&drag.ghost, (left, top), width);
+
+        let target = target_at(position).filter(|&to| game.can_drop(to));
+        if game.drop_target.get_untracked() != target {
+            game.drop_target.set(target);
+        }
+        Some(false)
+    });
+
+    // A finger that slides before the long press is scrolling the page
+    if moved == Some(true) {
+        cancel();
+    }
+}
+
+pub fn pointer_up(e: &PointerEvent, game: Solitaire) {
+    let Some(press) = PRESS.with(|press| {
+        let mut press = press.borrow_mut();
+        match press.as_ref() {
+            Some(p) if p.pointer == e.pointer_id() => press.take(),
+            _ => None,
+        }
+    }) else {
+        return;
+    };
+    if let Some(timer) = press.timer {
+        timer.clear();
+    }
+    let Some(drag) = press.drag else {
+        return;
+    };
+
+    SUPPRESS_CLICK.with(|suppress| *suppress.borrow_mut() = true);
+    let target = target_at(position(e)).filter(|&to| game.can_drop(to));
+    match target {
+        Some(to) => {
+            drag.ghost.remove();
+            _ = game.drop(press.source, to);
+        }
+        None => {
+            game.end_drag();
+            snap_back(drag, press.stack.client_width().into());
+        }
+    }
+}
+
+fn snap_back(drag: TouchDrag, width: f64) {
+    _ = drag.ghost.class_list().add_1("snap-back");
+    place(&drag.ghost, drag.origin, width);
+    set_timeout(move || drag.ghost.remove(), SNAP_BACK);
+}
+
+pub fn pointer_cancel(game: Solitaire) {
+    if is_dragging() {
+        game.end_drag();
+    }
+    cancel();
+}
+
+fn cancel() {
+    let press = PRESS.with(|press| press.borrow_mut().take());
+    if let Some(press) = press {
+        if let Some(timer) = press.timer {
+            timer.clear();
+        }
+        if let Some(drag) = press.drag {
+            drag.ghost.remove();
+        }
+    }
+}
+
+// Lifting a finger after a drag would otherwise also count as a tap
+pub fn touch_end(e: &TouchEvent) {
+    let suppress = SUPPRESS_CLICK.with(|suppress| suppress.replace(false));
+    if suppress {
+        e.prevent_default();
+    }
+}
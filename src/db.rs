@@ -0,0 +1,228 @@
+use crate::{
+    app::SavedGameSummary,
+    game::{GameRecord, SavedGame},
+};
+use rand::{distributions::Alphanumeric, Rng};
+use rusqlite::{params, Connection, OptionalExtension};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DbError {
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("No saved game with id {0}")]
+    NotFound(String),
+    #[error("{0:?} isn't a saved game id")]
+    InvalidId(String),
+}
+
+const ID_LEN: usize = 8;
+
+pub fn connect() -> Result<Connection, DbError> {
+    let path = std::env::var("SOLITAIRE_DB")
+        .unwrap_or_else(|_| "solitaire.db".to_owned());
+    let conn = Connection::open(path)?;
+    create_tables(&conn)?;
+
+    Ok(conn)
+}
+
+fn create_tables(conn: &Connection) -> Result<(), DbError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS saved_games (
+            id TEXT PRIMARY KEY,
+            seed TEXT NOT NULL,
+            moves INTEGER NOT NULL,
+            score INTEGER NOT NULL,
+            game TEXT NOT NULL,
+            saved_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS game_records (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            record TEXT NOT NULL,
+            recorded_at INTEGER NOT NULL
+        );",
+    )?;
+
+    Ok(())
+}
+
+fn new_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(ID_LEN)
+        .map(char::from)
+        .collect()
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+// Saving with an id overwrites that game. Clients only send back the id
+// their own earlier save was given; games opened from a shared link are
+// saved under a new one.
+pub fn save_game(
+    conn: &Connection,
+    id: Option<String>,
+    game: &SavedGame,
+) -> Result<String, DbError> {
+    let id = id.unwrap_or_else(new_id);
+    if !is_valid_id(&id) {
+        return Err(DbError::InvalidId(id));
+    }
+    conn.execute(
+        "INSERT INTO saved_games (id, seed, moves, score, game, saved_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, strftime('%s', 'now'))
+         ON CONFLICT (id) DO UPDATE SET
+            moves = excluded.moves,
+            score = excluded.score,
+            game = excluded.game,
+            saved_at = excluded.saved_at",
+        params![
+            id,
+            game.state.seed.to_string(),
+            game.state.moves,
+            game.state.score,
+            serde_json::to_string(game)?,
+        ],
+    )?;
+
+    Ok(id)
+}
+
+pub fn load_game(
+    conn: &Connection,
+    id: &str,
+) -> Result<SavedGame, DbError> {
+    let game: String = conn
+        .query_row(
+            "SELECT game FROM saved_games WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| DbError::NotFound(id.to_owned()))?;
+
+    Ok(serde_json::from_str(&game)?)
+}
+
+pub fn list_games(
+    conn: &Connection,
+) -> Result<Vec<SavedGameSummary>, DbError> {
+    let mut statement = conn.prepare(
+        "SELECT id, seed, moves, score FROM saved_games
+         ORDER BY saved_at DESC",
+    )?;
+    let games = statement
+        .query_map([], |row| {
+            Ok(SavedGameSummary {
+                id: row.get(0)?,
+                seed: row.get::<_, String>(1)?.parse().unwrap_or_default(),
+                moves: row.get(2)?,
+                score: row.get(3)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    Ok(games)
+}
+
+pub fn record_game(
+    conn: &Connection,
+    record: &GameRecord,
+) -> Result<(), DbError> {
+    conn.execute(
+        "INSERT INTO game_records (record, recorded_at)
+         VALUES (?1, strftime('%s', 'now'))",
+        params![serde_json::to_string(record)?],
+    )?;
+
+    Ok(())
+}
+
+pub fn list_records(
+    conn: &Connection,
+) -> Result<Vec<GameRecord>, DbError> {
+    let mut statement =
+        conn.prepare("SELECT record FROM game_records ORDER BY id")?;
+    let records = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .map(|record| Ok(serde_json::from_str(&record?)?))
+        .collect::<Result<_, DbError>>()?;
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{full_deck, GameState, Rules};
+
+    fn open() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        conn
+    }
+
+    fn game(seed: u64) -> SavedGame {
+        SavedGame {
+            state: GameState::new(
+                full_deck(),
+                Some(seed),
+                Rules::default(),
+            ),
+            history: Default::default(),
+            winnable: false,
+        }
+    }
+
+    #[test]
+    fn saves_loads_and_lists_games() {
+        let conn = open();
+        let first = save_game(&conn, None, &game(1)).unwrap();
+        let second = save_game(&conn, None, &game(2)).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(load_game(&conn, &first).unwrap(), game(1));
+
+        let mut seeds: Vec<_> = list_games(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|summary| (summary.id, summary.seed))
+            .collect();
+        seeds.sort_by_key(|&(_, seed)| seed);
+        assert_eq!(seeds, [(first, 1), (second, 2)]);
+    }
+
+    #[test]
+    fn saves_over_a_game_with_the_same_id() {
+        let conn = open();
+        let id = save_game(&conn, None, &game(1)).unwrap();
+        let mut moved = game(1);
+        moved.state.moves = 12;
+        assert_eq!(
+            save_game(&conn, Some(id.clone()), &moved).unwrap(),
+            id
+        );
+
+        assert_eq!(load_game(&conn, &id).unwrap(), moved);
+        assert_eq!(list_games(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_made_up_ids() {
+        let conn = open();
+        for id in ["", "abc", "../../etc", "abcdefgh1", "abc def!"] {
+            assert!(matches!(
+                save_game(&conn, Some(id.to_owned()), &game(1)),
+                Err(DbError::InvalidId(_))
+            ));
+        }
+        assert!(matches!(
+            load_game(&conn, "abcdefgh"),
+            Err(DbError::NotFound(_))
+        ));
+    }
+}
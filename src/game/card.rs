@@ -8,7 +8,8 @@ pub fn FaceDownCard() -> impl IntoView {
             class="card"
             draggable="false"
             clickable="false"
-            src="cards/face_down.jpg"
+            src="/cards/face_down.jpg"
+            alt="Face-down card"
         />
     }
 }
@@ -17,12 +18,23 @@ pub fn FaceDownCard() -> impl IntoView {
 pub fn CardOutline() -> impl IntoView {
     view! {
         <span class="card-outline">
-            <img src="cards/ace_of_spades.png" />
+            <img src="/cards/ace_of_spades.png" alt="" aria-hidden="true" />
         </span>
     }
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+)]
 pub enum Suit {
     Spades,
     Hearts,
@@ -36,6 +48,9 @@ pub struct Card {
     pub value: u8,
     filename: String,
     faceup: bool,
+    // Tells apart the duplicates of a multi-deck game
+    #[serde(default)]
+    copy: u8,
 }
 
 impl Card {
@@ -78,9 +93,19 @@ impl Card {
             value,
             filename,
             faceup: false,
+            copy: 0,
         }
     }
 
+    pub fn with_copy(mut self, copy: u8) -> Self {
+        self.copy = copy;
+        self
+    }
+
+    pub fn sort_key(&self) -> (Suit, u8, u8) {
+        (self.suit, self.value, self.copy)
+    }
+
     pub fn color(&self) -> &'static str {
         match self.suit {
             Suit::Spades | Suit::Clubs => "black",
@@ -88,16 +113,45 @@ impl Card {
         }
     }
 
+    pub fn name(&self) -> String {
+        let rank = match self.value {
+            1 => "ace".to_owned(),
+            11 => "jack".to_owned(),
+            12 => "queen".to_owned(),
+            13 => "king".to_owned(),
+            value => value.to_string(),
+        };
+        let suit = match self.suit {
+            Suit::Spades => "spades",
+            Suit::Hearts => "hearts",
+            Suit::Diamonds => "diamonds",
+            Suit::Clubs => "clubs",
+        };
+        format!("{rank} of {suit}")
+    }
+
+    pub fn label(&self) -> String {
+        if self.faceup {
+            format!("{}, face up", self.name())
+        } else {
+            "Face-down card".to_owned()
+        }
+    }
+
     fn filename(&self) -> String {
-        format!("cards/{}", self.filename)
+        format!("/cards/{}", self.filename)
     }
 
     pub fn flip(&mut self) {
         self.faceup = true;
     }
 
+    pub fn is_faceup(&self) -> bool {
+        self.faceup
+    }
+
     pub fn id(&self) -> String {
-        format!("{}-{}", self.filename, self.faceup)
+        format!("{}-{}-{}", self.filename, self.copy, self.faceup)
     }
 
     fn show_faceup(&self) -> View {
@@ -109,7 +163,10 @@ impl Card {
                 class="card"
                 class:selected=border
                 // on:click=click
+                draggable="false"
                 src=self.filename()
+                alt=self.label()
+                data-card=format!("{}-{}", self.filename, self.copy)
             />
         }
         .into_view()
@@ -0,0 +1,313 @@
+use crate::game::{
+    Card, GameState, Klondike, Layout, Move, MoveError, Suit, Variant,
+};
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+// FreeCell: one deck dealt face up into eight columns, plus four free
+// cells that each park a single card. Deals are numbered the way the
+// Microsoft version numbers them, so deal #617 here is deal #617 anywhere.
+
+const DEALS: RangeInclusive<u64> = 1..=32000;
+const DECK_SIZE: usize = 52;
+const PILES: usize = 8;
+const CELLS: usize = 4;
+const FOUNDATIONS: usize = 4;
+// Microsoft numbers the deck ace to king, each rank in this suit order
+const SUIT_ORDER: [Suit; 4] =
+    [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
+pub struct FreeCell;
+
+// The C runtime's `rand` that the original deals were made with
+struct Lcg(u32);
+
+impl Lcg {
+    fn next(&mut self) -> usize {
+        self.0 = self.0.wrapping_mul(214013).wrapping_add(2531011)
+            & 0x7fff_ffff;
+        (self.0 >> 16) as usize
+    }
+}
+
+impl FreeCell {
+    fn is_sequence(cards: &[Card]) -> bool {
+        cards.windows(2).all(|pair| {
+            Klondike::check_move_to_pile(&pair[1], &pair[..1]).is_ok()
+        })
+    }
+
+    // Moving several cards at once is shorthand for shuffling them one at
+    // a time through the free cells and empty columns
+    fn supermove_limit(state: &GameState, to: usize) -> usize {
+        let cells = state.cells.iter().filter(|c| c.is_empty()).count();
+        let columns = state
+            .piles
+            .iter()
+            .enumerate()
+            .filter(|&(idx, pile)| idx != to && pile.is_empty())
+            .count();
+        (cells + 1) << columns
+    }
+
+    fn check_move_to_column(
+        card: &Card,
+        to: &[Card],
+    ) -> Result<(), MoveError> {
+        match to {
+            [] => Ok(()),
+            to => Klondike::check_move_to_pile(card, to),
+        }
+    }
+}
+
+impl Variant for FreeCell {
+    fn random_seed(&self) -> u64 {
+        rand::thread_rng().gen_range(DEALS)
+    }
+
+    // Only the original 32,000 deals are numbered, and `Lcg` would wrap
+    // larger seeds around onto them
+    fn is_valid_seed(&self, seed: u64) -> bool {
+        DEALS.contains(&seed)
+    }
+
+    // Puts `cards` in the order they are dealt, row by row
+    fn shuffle(&self, cards: &mut Vec<Card>, seed: u64) {
+        let mut deck: Vec<_> = (0..DECK_SIZE)
+            .rev()
+            .filter_map(|i| {
+                let suit = SUIT_ORDER[i % 4];
+                let value = (i / 4 + 1) as u8;
+                cards
+                    .iter()
+                    .find(|card| card.suit == suit && card.value == value)
+                    .cloned()
+            })
+            .collect();
+
+        let mut rng = Lcg(seed as u32);
+        for i in 0..deck.len() - 1 {
+            let j = deck.len() - 1 - rng.next() % (deck.len() - i);
+            deck.swap(i, j);
+        }
+        *cards = deck;
+    }
+
+    fn layout(&self, cards: &mut Vec<Card>) -> Layout {
+        let mut piles = vec![Vec::new(); PILES];
+        for (i, mut card) in cards.drain(..).enumerate() {
+            card.flip();
+            piles[i % PILES].push(card);
+        }
+        Layout {
+            piles,
+            foundations: vec![Vec::new(); FOUNDATIONS],
+            cells: vec![Vec::new(); CELLS],
+        }
+    }
+
+    fn check(&self, state: &GameState, m: Move) -> Result<(), MoveError> {
+        match m {
+            Move::PileToPile { from, n, to } => {
+                let source = state.pile(from)?;
+                let target = state.pile(to)?;
+                if from == to {
+                    return Err(MoveError::SamePile);
+                }
+                if n == 0 || n > source.len() {
+                    return Err(MoveError::EmptySource);
+                }
+                let run = &source[source.len() - n..];
+                if !Self::is_sequence(run) {
+                    return Err(MoveError::NotASequence);
+                }
+                if n > Self::supermove_limit(state, to) {
+                    return Err(MoveError::TooManyCards);
+                }
+                Self::check_move_to_column(&run[0], target)
+            }
+            Move::PileToFoundation { from, to } => {
+                let card = state
+                    .pile(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                Klondike::check_move_to_foundation(
+                    card,
+                    state.foundation(to)?,
+                )
+            }
+            Move::PileToCell { from, to } => {
+                if state.pile(from)?.is_empty() {
+                    return Err(MoveError::EmptySource);
+                }
+                match state.cell(to)?.is_empty() {
+                    true => Ok(()),
+                    false => Err(MoveError::CellTaken),
+                }
+            }
+            Move::CellToPile { from, to } => {
+                let card = state
+                    .cell(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                Self::check_move_to_column(card, state.pile(to)?)
+            }
+            Move::CellToFoundation { from, to } => {
+                let card = state
+                    .cell(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                Klondike::check_move_to_foundation(
+                    card,
+                    state.foundation(to)?,
+                )
+            }
+            Move::Draw => Err(MoveError::NothingToDraw),
+            _ => Err(MoveError::Unsupported),
+        }
+    }
+
+    fn has_stock(&self) -> bool {
+        false
+    }
+
+    fn foundation_for(
+        &self,
+        state: &GameState,
+        card: &Card,
+    ) -> Option<usize> {
+        Klondike::find_foundation(state, card)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        state::tests::{board, cards, full_deck, names},
+        Rules, VariantKind,
+    };
+
+    fn rules() -> Rules {
+        Rules {
+            variant: VariantKind::FreeCell,
+            ..Default::default()
+        }
+    }
+
+    fn deal(seed: u64) -> GameState {
+        GameState::new(full_deck(), Some(seed), rules())
+    }
+
+    // A FreeCell board with every column and cell taken, to clear by hand
+    fn full_board() -> GameState {
+        let mut state = board(rules());
+        state.piles = vec![cards("KD"); PILES];
+        state.cells = vec![cards("KC"); CELLS];
+        state
+    }
+
+    fn check(state: &GameState, m: Move) -> Result<(), MoveError> {
+        FreeCell.check(state, m)
+    }
+
+    fn first_row(state: &GameState) -> Vec<String> {
+        let row: Vec<_> =
+            state.piles.iter().map(|pile| pile[0].clone()).collect();
+        names(&row)
+    }
+
+    #[test]
+    fn matches_the_microsoft_deals() {
+        let row = |row: &str| names(&cards(row));
+        assert_eq!(first_row(&deal(1)), row("JD 2D 9H JC 5D 7H 7C 5H"));
+        assert_eq!(first_row(&deal(617)), row("7D AD 5C 3S 5S 8C 2D AH"));
+    }
+
+    #[test]
+    fn replaces_seeds_outside_the_numbered_deals() {
+        for seed in [0, 32001, 1 << 32 | 1] {
+            assert!(DEALS.contains(&deal(seed).seed));
+        }
+        assert_eq!(deal(32000).seed, 32000);
+    }
+
+    #[test]
+    fn moves_more_cards_with_more_space() {
+        let mut state = full_board();
+        assert_eq!(FreeCell::supermove_limit(&state, 0), 1);
+
+        state.cells[0].clear();
+        state.cells[1].clear();
+        assert_eq!(FreeCell::supermove_limit(&state, 0), 3);
+
+        state.piles[6].clear();
+        state.piles[7].clear();
+        assert_eq!(FreeCell::supermove_limit(&state, 0), 3 << 2);
+        // The column being moved into doesn't count as spare room
+        assert_eq!(FreeCell::supermove_limit(&state, 7), 3 << 1);
+    }
+
+    #[test]
+    fn checks_stacks_against_the_space_to_move_them() {
+        let mut state = full_board();
+        state.piles[0] = cards("9C 8H 7S");
+        state.piles[1] = cards("10H");
+        state.piles[2] = cards("9C 8C");
+        state.piles[3] = cards("9S");
+
+        let stack = |state: &GameState, from, n, to| {
+            check(state, Move::PileToPile { from, n, to })
+        };
+        assert_eq!(stack(&state, 0, 3, 1), Err(MoveError::TooManyCards));
+        assert_eq!(stack(&state, 0, 1, 1), Err(MoveError::WrongRank));
+        assert_eq!(stack(&state, 2, 2, 1), Err(MoveError::NotASequence));
+        assert_eq!(stack(&state, 2, 1, 3), Err(MoveError::WrongColour));
+
+        state.cells[0].clear();
+        state.cells[1].clear();
+        assert_eq!(stack(&state, 0, 3, 1), Ok(()));
+    }
+
+    #[test]
+    fn takes_any_card_into_an_empty_column() {
+        let mut state = full_board();
+        state.piles[0] = cards("5H");
+        state.piles[1].clear();
+
+        assert_eq!(
+            check(
+                &state,
+                Move::PileToPile {
+                    from: 0,
+                    n: 1,
+                    to: 1
+                }
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(&state, Move::CellToPile { from: 0, to: 1 }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn parks_one_card_per_cell() {
+        let mut state = full_board();
+        state.cells[1].clear();
+
+        let park = |to| check(&state, Move::PileToCell { from: 0, to });
+        assert_eq!(park(0), Err(MoveError::CellTaken));
+        assert_eq!(park(1), Ok(()));
+    }
+
+    #[test]
+    fn has_no_stock() {
+        assert_eq!(
+            check(&deal(1), Move::Draw),
+            Err(MoveError::NothingToDraw)
+        );
+    }
+}
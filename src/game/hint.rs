@@ -0,0 +1,104 @@
+use crate::game::{Card, GameState, Move};
+
+impl GameState {
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let piles = 0..self.piles.len();
+        let foundations = 0..self.foundations.len();
+        let cells = 0..self.cells.len();
+        let mut moves = vec![Move::Draw];
+
+        for from in piles.clone() {
+            let faceup = self.piles[from]
+                .iter()
+                .rev()
+                .take_while(|card| card.is_faceup())
+                .count();
+            for n in 1..=faceup {
+                moves.extend(piles.clone().map(|to| Move::PileToPile {
+                    from,
+                    n,
+                    to,
+                }));
+            }
+            moves.extend(
+                foundations
+                    .clone()
+                    .map(|to| Move::PileToFoundation { from, to }),
+            );
+            moves.extend(
+                cells.clone().map(|to| Move::PileToCell { from, to }),
+            );
+        }
+        for from in foundations.clone() {
+            moves.extend(
+                piles
+                    .clone()
+                    .map(|to| Move::FoundationToPile { from, to }),
+            );
+        }
+        for from in cells {
+            moves.extend(
+                piles.clone().map(|to| Move::CellToPile { from, to }),
+            );
+            moves.extend(
+                foundations
+                    .clone()
+                    .map(|to| Move::CellToFoundation { from, to }),
+            );
+        }
+        moves.extend(piles.map(|to| Move::WasteToPile { to }));
+        moves.extend(foundations.map(|to| Move::WasteToFoundation { to }));
+
+        moves.retain(|&m| self.check(m).is_ok());
+        moves
+    }
+
+    fn usefulness(&self, m: Move) -> i32 {
+        let hidden = |pile: &[Card]| {
+            pile.iter().filter(|card| !card.is_faceup()).count() as i32
+        };
+
+        match m {
+            Move::PileToFoundation { from, .. } => {
+                let pile = &self.piles[from];
+                let reveals =
+                    pile.len() >= 2 && !pile[pile.len() - 2].is_faceup();
+                if reveals {
+                    60 + hidden(pile)
+                } else {
+                    50
+                }
+            }
+            Move::WasteToFoundation { .. } => 45,
+            Move::CellToFoundation { .. } => 45,
+            Move::PileToPile { from, n, .. } => {
+                let pile = &self.piles[from];
+                match pile.len() - n {
+                    0 if pile[0].value == 13 => 0,
+                    0 => 35,
+                    rest if !pile[rest - 1].is_faceup() => {
+                        40 + hidden(pile)
+                    }
+                    _ => 5,
+                }
+            }
+            Move::WasteToPile { .. } => 30,
+            Move::CellToPile { .. } => 25,
+            Move::Draw => 2,
+            Move::PileToCell { .. } => 1,
+            Move::FoundationToPile { .. } => 1,
+        }
+    }
+
+    pub fn hints(&self) -> Vec<Move> {
+        let mut moves: Vec<_> = self
+            .legal_moves()
+            .into_iter()
+            .map(|m| (self.usefulness(m), m))
+            .filter(|(usefulness, _)| *usefulness > 0)
+            .collect();
+        moves.sort_by_key(|(usefulness, _)| -usefulness);
+
+        moves.into_iter().map(|(_, m)| m).collect()
+    }
+}
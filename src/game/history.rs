@@ -0,0 +1,137 @@
+use crate::game::{GameState, Move};
+use serde::{Deserialize, Serialize};
+
+// Undo history as the moves played from a starting state, which keeps it
+// small enough to save after every move. Undoing replays all but the last
+// move; moves are deterministic, so this lands on the same board.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    start: Option<GameState>,
+    #[serde(default)]
+    done: Vec<Move>,
+    #[serde(default)]
+    undone: Vec<Move>,
+}
+
+impl History {
+    // `previous` is the state `m` was played from
+    pub fn record(&mut self, previous: GameState, m: Move) {
+        if self.done.is_empty() {
+            self.start = Some(previous);
+        }
+        self.done.push(m);
+        self.undone.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // The number of moves played, which changes with every move, undo and
+    // redo
+    pub fn len(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.done.is_empty()
+    }
+
+    // Leaves the history alone unless the replay works
+    pub fn undo(&mut self, _current: GameState) -> Option<GameState> {
+        let start = self.start.clone()?;
+        let (&m, rest) = self.done.split_last()?;
+        let previous = rest
+            .iter()
+            .try_fold(start, |state, &m| state.apply(m))
+            .ok()?;
+        self.done.pop();
+        self.undone.push(m);
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: GameState) -> Option<GameState> {
+        let &m = self.undone.last()?;
+        let next = current.apply(m).ok()?;
+        self.undone.pop();
+        self.done.push(m);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{state::tests::full_deck, Rules};
+
+    fn play(state: &GameState, history: &mut History) -> GameState {
+        let m = state.hints()[0];
+        history.record(state.clone(), m);
+        state.apply(m).unwrap()
+    }
+
+    #[test]
+    fn undoes_and_redoes_by_replaying_moves() {
+        let mut history = History::default();
+        let start = GameState::new(full_deck(), Some(3), Rules::default());
+        let first = play(&start, &mut history);
+        let second = play(&first, &mut history);
+
+        assert_eq!(history.undo(second.clone()), Some(first.clone()));
+        assert_eq!(history.undo(first.clone()), Some(start.clone()));
+        assert_eq!(history.undo(start.clone()), None);
+        assert_eq!(history.redo(start), Some(first.clone()));
+        assert_eq!(history.redo(first), Some(second));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn saves_moves_rather_than_boards() {
+        let mut history = History::default();
+        let mut state =
+            GameState::new(full_deck(), Some(3), Rules::default());
+        let one = {
+            state = play(&state, &mut history);
+            serde_json::to_string(&history).unwrap().len()
+        };
+        for _ in 0..20 {
+            state = play(&state, &mut history);
+        }
+        let many = serde_json::to_string(&history).unwrap().len();
+        assert!(many < one * 2, "{one} bytes grew to {many}");
+    }
+
+    #[test]
+    fn keeps_the_history_when_a_replay_fails() {
+        let mut history = History::default();
+        let start = GameState::new(full_deck(), Some(3), Rules::default());
+        let first = play(&start, &mut history);
+        play(&first, &mut history);
+        // A move that can't be played from the start, as from a corrupt save
+        history.done.insert(
+            0,
+            Move::PileToPile {
+                from: 0,
+                n: 9,
+                to: 1,
+            },
+        );
+
+        let before = history.clone();
+        assert_eq!(history.undo(first.clone()), None);
+        assert_eq!(history, before);
+        history.undone.push(Move::PileToPile {
+            from: 0,
+            n: 9,
+            to: 1,
+        });
+        let before = history.clone();
+        assert_eq!(history.redo(first), None);
+        assert_eq!(history, before);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::game::{
+    Card, GameState, Layout, Move, MoveError, Outcome, Rules, Variant,
+};
+
+// Klondike: seven piles of one to seven cards, built down in alternating
+// colours, with the rest of the deck drawn through a waste pile
+
+const PILES: usize = 7;
+const FOUNDATIONS: usize = 4;
+
+pub struct Klondike;
+
+impl Klondike {
+    // Building down in alternating colours and up by suit, which FreeCell
+    // plays by too
+    pub(super) fn check_move_to_pile(
+        card: &Card,
+        to: &[Card],
+    ) -> Result<(), MoveError> {
+        let Some(to_card) = to.last() else {
+            return match card.value {
+                13 => Ok(()),
+                _ => Err(MoveError::NotKing),
+            };
+        };
+
+        if card.color() == to_card.color() {
+            Err(MoveError::WrongColour)
+        } else if card.value + 1 != to_card.value {
+            Err(MoveError::WrongRank)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(super) fn check_move_to_foundation(
+        card: &Card,
+        to: &[Card],
+    ) -> Result<(), MoveError> {
+        let Some(to_card) = to.last() else {
+            return match card.value {
+                1 => Ok(()),
+                _ => Err(MoveError::NotAce),
+            };
+        };
+
+        if card.suit != to_card.suit {
+            Err(MoveError::WrongSuit)
+        } else if card.value != to_card.value + 1 {
+            Err(MoveError::WrongRank)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(super) fn find_foundation(
+        state: &GameState,
+        card: &Card,
+    ) -> Option<usize> {
+        state.foundations.iter().position(|foundation| {
+            Self::check_move_to_foundation(card, foundation).is_ok()
+        })
+    }
+
+    fn can_recycle(state: &GameState) -> bool {
+        state
+            .rules
+            .passes
+            .max_recycles()
+            .map_or(true, |max| state.recycles < max)
+    }
+}
+
+impl Variant for Klondike {
+    fn layout(&self, cards: &mut Vec<Card>) -> Layout {
+        Layout {
+            piles: (0..PILES)
+                .map(|i| cards.drain(0..=i).collect())
+                .collect(),
+            foundations: vec![Vec::new(); FOUNDATIONS],
+            cells: Vec::new(),
+        }
+    }
+
+    fn initial_score(&self, rules: &Rules) -> i32 {
+        rules.scoring.initial()
+    }
+
+    fn check(&self, state: &GameState, m: Move) -> Result<(), MoveError> {
+        match m {
+            Move::PileToPile { from, n, to } => {
+                let source = state.pile(from)?;
+                let target = state.pile(to)?;
+                if from == to {
+                    return Err(MoveError::SamePile);
+                }
+                if n == 0 || n > source.len() {
+                    return Err(MoveError::EmptySource);
+                }
+                let card = &source[source.len() - n];
+                if !card.is_faceup() {
+                    return Err(MoveError::FaceDown);
+                }
+                Self::check_move_to_pile(card, target)
+            }
+            Move::PileToFoundation { from, to } => {
+                let card = state
+                    .pile(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                Self::check_move_to_foundation(card, state.foundation(to)?)
+            }
+            Move::FoundationToPile { from, to } => {
+                let card = state
+                    .foundation(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                Self::check_move_to_pile(card, state.pile(to)?)
+            }
+            Move::WasteToPile { to } => {
+                let card =
+                    state.waste.last().ok_or(MoveError::EmptySource)?;
+                Self::check_move_to_pile(card, state.pile(to)?)
+            }
+            Move::WasteToFoundation { to } => {
+                let card =
+                    state.waste.last().ok_or(MoveError::EmptySource)?;
+                Self::check_move_to_foundation(card, state.foundation(to)?)
+            }
+            Move::Draw if !state.deck.is_empty() => Ok(()),
+            Move::Draw if state.waste.is_empty() => {
+                Err(MoveError::NothingToDraw)
+            }
+            Move::Draw if !Self::can_recycle(state) => {
+                Err(MoveError::NoPassesLeft)
+            }
+            Move::Draw => Ok(()),
+            Move::PileToCell { .. }
+            | Move::CellToPile { .. }
+            | Move::CellToFoundation { .. } => Err(MoveError::Unsupported),
+        }
+    }
+
+    fn has_waste(&self) -> bool {
+        true
+    }
+
+    fn draw(&self, state: &mut GameState) -> Outcome {
+        if state.deck.is_empty() {
+            state.deck = state.waste.drain(..).rev().collect();
+            state.recycles += 1;
+            return Outcome {
+                recycled: true,
+                ..Default::default()
+            };
+        }
+
+        let n = state.rules.draw.count().min(state.deck.len());
+        let drawn = state.deck.drain(state.deck.len() - n..).rev();
+        state.waste.extend(drawn.map(|mut card| {
+            card.flip();
+            card
+        }));
+
+        Outcome::default()
+    }
+
+    fn is_stock_exhausted(&self, state: &GameState) -> bool {
+        state.deck.is_empty()
+            && (state.waste.is_empty() || !Self::can_recycle(state))
+    }
+
+    fn settle(
+        &self,
+        previous: &GameState,
+        next: &mut GameState,
+        m: Move,
+        outcome: Outcome,
+    ) {
+        let rules = previous.rules;
+        next.score =
+            rules.scoring.score(previous.score, m, outcome, rules.draw);
+    }
+
+    fn foundation_for(
+        &self,
+        state: &GameState,
+        card: &Card,
+    ) -> Option<usize> {
+        Self::find_foundation(state, card)
+    }
+
+    fn can_auto_complete(&self, state: &GameState) -> bool {
+        state.deck.is_empty()
+            && state.waste.is_empty()
+            && state.piles.iter().flatten().all(Card::is_faceup)
+    }
+
+    // Plays the lowest card that can go up first
+    fn next_auto_complete_move(&self, state: &GameState) -> Option<Move> {
+        state
+            .piles
+            .iter()
+            .enumerate()
+            .filter_map(|(from, pile)| {
+                let card = pile.last()?;
+                let to = Self::find_foundation(state, card)?;
+                Some((card.value, Move::PileToFoundation { from, to }))
+            })
+            .min_by_key(|(value, _)| *value)
+            .map(|(_, m)| m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        state::tests::{board, card, cards, down},
+        PassLimit,
+    };
+
+    fn check(state: &GameState, m: Move) -> Result<(), MoveError> {
+        Klondike.check(state, m)
+    }
+
+    #[test]
+    fn builds_down_in_alternating_colours() {
+        let mut state = board(Rules::default());
+        state.piles[0] = cards("9H");
+        state.piles[1] = cards("8S");
+        state.piles[2] = cards("8D");
+        state.piles[3] = cards("7C");
+
+        let onto =
+            |from, to| check(&state, Move::PileToPile { from, n: 1, to });
+        assert_eq!(onto(1, 0), Ok(()));
+        assert_eq!(onto(2, 0), Err(MoveError::WrongColour));
+        assert_eq!(onto(3, 0), Err(MoveError::WrongRank));
+        assert_eq!(onto(0, 0), Err(MoveError::SamePile));
+    }
+
+    #[test]
+    fn only_kings_go_on_empty_piles() {
+        let mut state = board(Rules::default());
+        state.piles[0] = cards("KH");
+        state.piles[1] = cards("QS");
+
+        let onto =
+            |from| check(&state, Move::PileToPile { from, n: 1, to: 2 });
+        assert_eq!(onto(0), Ok(()));
+        assert_eq!(onto(1), Err(MoveError::NotKing));
+    }
+
+    #[test]
+    fn checks_the_card_a_stack_starts_from() {
+        let mut state = board(Rules::default());
+        state.piles[0] = vec![down("4C"), card("9H"), card("8S")];
+        state.piles[1] = cards("10S");
+
+        let stack =
+            |n| check(&state, Move::PileToPile { from: 0, n, to: 1 });
+        assert_eq!(stack(2), Ok(()));
+        assert_eq!(stack(3), Err(MoveError::FaceDown));
+        assert_eq!(stack(4), Err(MoveError::EmptySource));
+        assert_eq!(stack(0), Err(MoveError::EmptySource));
+    }
+
+    #[test]
+    fn builds_foundations_up_by_suit() {
+        let mut state = board(Rules::default());
+        state.foundations[0] = cards("AH");
+        state.piles[0] = cards("2H");
+        state.piles[1] = cards("2D");
+        state.piles[2] = cards("3H");
+        state.piles[3] = cards("2S");
+
+        let up =
+            |from, to| check(&state, Move::PileToFoundation { from, to });
+        assert_eq!(up(0, 0), Ok(()));
+        assert_eq!(up(1, 0), Err(MoveError::WrongSuit));
+        assert_eq!(up(2, 0), Err(MoveError::WrongRank));
+        assert_eq!(up(3, 1), Err(MoveError::NotAce));
+        assert_eq!(up(4, 1), Err(MoveError::EmptySource));
+    }
+
+    #[test]
+    fn takes_cards_back_off_foundations() {
+        let mut state = board(Rules::default());
+        state.foundations[0] = cards("AH 2H");
+        state.piles[0] = cards("3C");
+        state.piles[1] = cards("3D");
+
+        let down =
+            |from, to| check(&state, Move::FoundationToPile { from, to });
+        assert_eq!(down(0, 0), Ok(()));
+        assert_eq!(down(0, 1), Err(MoveError::WrongColour));
+        assert_eq!(down(1, 0), Err(MoveError::EmptySource));
+    }
+
+    #[test]
+    fn plays_the_top_of_the_waste() {
+        let mut state = board(Rules::default());
+        assert_eq!(
+            check(&state, Move::WasteToPile { to: 0 }),
+            Err(MoveError::EmptySource)
+        );
+        assert_eq!(
+            check(&state, Move::WasteToFoundation { to: 0 }),
+            Err(MoveError::EmptySource)
+        );
+
+        state.waste = cards("AS QH");
+        assert_eq!(
+            check(&state, Move::WasteToPile { to: 0 }),
+            Err(MoveError::NotKing)
+        );
+        assert_eq!(
+            check(&state, Move::WasteToFoundation { to: 0 }),
+            Err(MoveError::NotAce)
+        );
+        state.piles[0] = cards("KC");
+        assert_eq!(check(&state, Move::WasteToPile { to: 0 }), Ok(()));
+    }
+
+    #[test]
+    fn limits_passes_through_the_deck() {
+        let rules = Rules {
+            passes: PassLimit::One,
+            ..Default::default()
+        };
+        let mut state = board(rules);
+        assert_eq!(
+            check(&state, Move::Draw),
+            Err(MoveError::NothingToDraw)
+        );
+        assert!(Klondike.is_stock_exhausted(&state));
+
+        state.deck = vec![down("5C")];
+        assert_eq!(check(&state, Move::Draw), Ok(()));
+        let state = state.apply(Move::Draw).unwrap();
+        assert_eq!(
+            check(&state, Move::Draw),
+            Err(MoveError::NoPassesLeft)
+        );
+        assert!(Klondike.is_stock_exhausted(&state));
+
+        let unlimited = GameState {
+            rules: Rules::default(),
+            ..state
+        };
+        assert_eq!(check(&unlimited, Move::Draw), Ok(()));
+        assert!(!Klondike.is_stock_exhausted(&unlimited));
+    }
+
+    #[test]
+    fn auto_completes_the_lowest_card_first() {
+        let mut state = board(Rules::default());
+        state.foundations[0] = cards("AH");
+        state.piles[0] = cards("3H");
+        state.piles[1] = cards("2H");
+        state.piles[2] = cards("AS");
+
+        assert_eq!(Klondike.foundation_for(&state, &card("2H")), Some(0));
+        assert_eq!(Klondike.foundation_for(&state, &card("3H")), None);
+        assert_eq!(
+            Klondike.next_auto_complete_move(&state),
+            Some(Move::PileToFoundation { from: 2, to: 1 })
+        );
+    }
+
+    #[test]
+    fn has_no_free_cells() {
+        let mut state = board(Rules::default());
+        state.piles[0] = cards("5C");
+        for m in [
+            Move::PileToCell { from: 0, to: 0 },
+            Move::CellToPile { from: 0, to: 0 },
+            Move::CellToFoundation { from: 0, to: 0 },
+        ] {
+            assert_eq!(check(&state, m), Err(MoveError::Unsupported));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::game::{Card, GameState, Move, VariantKind};
+
+// Text for screen readers: labels for each part of the board and short
+// descriptions of moves for the live region
+
+fn count(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    }
+}
+
+fn top_card(cards: &[Card]) -> String {
+    match cards.last() {
+        Some(card) => format!("{} on top", card.name()),
+        None => "empty".to_owned(),
+    }
+}
+
+fn top_name(cards: &[Card]) -> String {
+    cards.last().map(Card::name).unwrap_or_default()
+}
+
+pub fn pile_label(idx: usize, cards: &[Card]) -> String {
+    let hidden = cards.iter().filter(|card| !card.is_faceup()).count();
+    match cards.len() {
+        0 => format!("Pile {}, empty", idx + 1),
+        len => format!(
+            "Pile {}, {}, {hidden} hidden, {}",
+            idx + 1,
+            count(len, "card"),
+            top_card(cards)
+        ),
+    }
+}
+
+pub fn foundation_label(idx: usize, cards: &[Card]) -> String {
+    format!("Foundation {}, {}", idx + 1, top_card(cards))
+}
+
+pub fn cell_label(idx: usize, cards: &[Card]) -> String {
+    match cards.last() {
+        Some(card) => format!("Free cell {}, {}", idx + 1, card.name()),
+        None => format!("Free cell {}, empty", idx + 1),
+    }
+}
+
+pub fn deck_label(cards: &[Card]) -> String {
+    match cards.len() {
+        0 => "Stock, empty".to_owned(),
+        len => format!("Stock, {}", count(len, "card")),
+    }
+}
+
+pub fn waste_label(cards: &[Card]) -> String {
+    format!("Waste, {}", top_card(cards))
+}
+
+impl GameState {
+    fn pile_name(&self, idx: usize) -> String {
+        match self.piles[idx].last() {
+            Some(card) => card.name(),
+            None => format!("empty pile {}", idx + 1),
+        }
+    }
+
+    // `next` is the state after `m` was applied to `self`
+    pub fn describe(&self, m: Move, next: &GameState) -> String {
+        let revealed = |from: usize| {
+            let idx = next.piles[from].len().checked_sub(1)?;
+            let card = &next.piles[from][idx];
+            (!self.piles[from][idx].is_faceup())
+                .then(|| format!(", revealing {}", card.name()))
+        };
+        let foundation = |to: usize| format!("foundation {}", to + 1);
+        let cell = |idx: usize| format!("free cell {}", idx + 1);
+
+        let description = match m {
+            Move::PileToPile { from, n, to } => {
+                let card = &self.piles[from][self.piles[from].len() - n];
+                let rest = match n {
+                    1 => String::new(),
+                    n => format!(" and {}", count(n - 1, "card")),
+                };
+                format!(
+                    "Moved {}{rest} onto {}{}",
+                    card.name(),
+                    self.pile_name(to),
+                    revealed(from).unwrap_or_default()
+                )
+            }
+            Move::PileToFoundation { from, to } => format!(
+                "Moved {} to {}{}",
+                self.pile_name(from),
+                foundation(to),
+                revealed(from).unwrap_or_default()
+            ),
+            Move::FoundationToPile { from, to } => format!(
+                "Moved {} from {} onto {}",
+                top_name(&self.foundations[from]),
+                foundation(from),
+                self.pile_name(to)
+            ),
+            Move::WasteToPile { to } => format!(
+                "Moved {} onto {}",
+                top_name(&self.waste),
+                self.pile_name(to)
+            ),
+            Move::WasteToFoundation { to } => format!(
+                "Moved {} to {}",
+                top_name(&self.waste),
+                foundation(to)
+            ),
+            Move::PileToCell { from, to } => {
+                format!("Moved {} to {}", self.pile_name(from), cell(to))
+            }
+            Move::CellToPile { from, to } => format!(
+                "Moved {} from {} onto {}",
+                top_name(&self.cells[from]),
+                cell(from),
+                self.pile_name(to)
+            ),
+            Move::CellToFoundation { from, to } => format!(
+                "Moved {} from {} to {}",
+                top_name(&self.cells[from]),
+                cell(from),
+                foundation(to)
+            ),
+            Move::Draw if !self.rules.has_waste() => {
+                "Dealt a new row".to_owned()
+            }
+            Move::Draw if self.deck.is_empty() => {
+                "Turned the waste over".to_owned()
+            }
+            Move::Draw => format!("Drew {}", top_name(&next.waste)),
+        };
+
+        let runs = |state: &GameState| {
+            state.foundations.iter().filter(|f| !f.is_empty()).count()
+        };
+        match self.rules.variant {
+            VariantKind::Spider(_) if runs(next) > runs(self) => {
+                format!("{description}, completing a run")
+            }
+            _ => description,
+        }
+    }
+}
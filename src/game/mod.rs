@@ -1,5 +1,41 @@
 mod solitaire;
-pub use solitaire::{Selection, Solitaire};
+pub use solitaire::{Direction, SavedGame, Selection, Solitaire};
+
+mod state;
+#[cfg(test)]
+pub(crate) use state::tests::full_deck;
+pub use state::{GameState, Move, MoveError};
+
+mod variant;
+pub use variant::{Layout, Variant};
+
+mod klondike;
+pub use klondike::Klondike;
+
+mod spider;
+pub use spider::Spider;
+
+mod freecell;
+pub use freecell::FreeCell;
+
+mod hint;
+
+pub mod labels;
+
+mod solver;
+pub use solver::{solve, Solution};
+
+mod rules;
+pub use rules::{DrawMode, PassLimit, Rules, SpiderSuits, VariantKind};
+
+mod score;
+pub use score::{Outcome, Scoring};
+
+mod stats;
+pub use stats::{GameRecord, GameResult, ModeStats, Stats};
+
+mod history;
+pub use history::History;
 
 mod card;
-pub use card::{Card, CardOutline, FaceDownCard};
+pub use card::{Card, CardOutline, FaceDownCard, Suit};
@@ -0,0 +1,278 @@
+use crate::game::{FreeCell, Klondike, Scoring, Spider, Suit, Variant};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum DrawMode {
+    #[default]
+    One,
+    Three,
+}
+
+impl DrawMode {
+    pub fn count(self) -> usize {
+        match self {
+            DrawMode::One => 1,
+            DrawMode::Three => 3,
+        }
+    }
+}
+
+impl fmt::Display for DrawMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count())
+    }
+}
+
+impl FromStr for DrawMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(DrawMode::One),
+            "3" => Ok(DrawMode::Three),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum PassLimit {
+    #[default]
+    Unlimited,
+    Three,
+    One,
+}
+
+impl PassLimit {
+    pub fn max_recycles(self) -> Option<u32> {
+        match self {
+            PassLimit::Unlimited => None,
+            PassLimit::Three => Some(2),
+            PassLimit::One => Some(0),
+        }
+    }
+}
+
+impl fmt::Display for PassLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassLimit::Unlimited => write!(f, "unlimited"),
+            PassLimit::Three => write!(f, "3"),
+            PassLimit::One => write!(f, "1"),
+        }
+    }
+}
+
+impl FromStr for PassLimit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unlimited" => Ok(PassLimit::Unlimited),
+            "3" => Ok(PassLimit::Three),
+            "1" => Ok(PassLimit::One),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum SpiderSuits {
+    #[default]
+    One,
+    Two,
+    Four,
+}
+
+impl SpiderSuits {
+    pub fn suits(self) -> &'static [Suit] {
+        match self {
+            SpiderSuits::One => &[Suit::Spades],
+            SpiderSuits::Two => &[Suit::Spades, Suit::Hearts],
+            SpiderSuits::Four => {
+                &[Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs]
+            }
+        }
+    }
+}
+
+impl fmt::Display for SpiderSuits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.suits().len())
+    }
+}
+
+impl FromStr for SpiderSuits {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(SpiderSuits::One),
+            "2" => Ok(SpiderSuits::Two),
+            "4" => Ok(SpiderSuits::Four),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum VariantKind {
+    #[default]
+    Klondike,
+    Spider(SpiderSuits),
+    FreeCell,
+}
+
+// Which game is being played, as saved with a deal; `variant` looks up
+// the rules that go with it
+impl VariantKind {
+    pub fn variant(self) -> &'static dyn Variant {
+        match self {
+            VariantKind::Klondike => &Klondike,
+            VariantKind::Spider(_) => &Spider,
+            VariantKind::FreeCell => &FreeCell,
+        }
+    }
+}
+
+impl fmt::Display for VariantKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariantKind::Klondike => write!(f, "klondike"),
+            VariantKind::Spider(_) => write!(f, "spider"),
+            VariantKind::FreeCell => write!(f, "freecell"),
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub struct Rules {
+    #[serde(default)]
+    pub variant: VariantKind,
+    pub draw: DrawMode,
+    pub passes: PassLimit,
+    pub scoring: Scoring,
+}
+
+impl Rules {
+    // The draw, pass and scoring options only apply to Klondike
+    pub fn from_query(get: impl Fn(&str) -> Option<String>) -> Self {
+        let parse = |key| get(key).unwrap_or_default();
+        match get("variant").as_deref() {
+            Some("spider") => Self {
+                variant: VariantKind::Spider(
+                    parse("suits").parse().unwrap_or_default(),
+                ),
+                ..Default::default()
+            },
+            Some("freecell") => Self {
+                variant: VariantKind::FreeCell,
+                ..Default::default()
+            },
+            _ => Self {
+                variant: VariantKind::Klondike,
+                draw: parse("draw").parse().unwrap_or_default(),
+                passes: parse("passes").parse().unwrap_or_default(),
+                scoring: parse("scoring").parse().unwrap_or_default(),
+            },
+        }
+    }
+
+    pub fn has_waste(&self) -> bool {
+        self.variant.variant().has_waste()
+    }
+
+    pub fn has_stock(&self) -> bool {
+        self.variant.variant().has_stock()
+    }
+
+    pub fn query(&self) -> String {
+        match self.variant {
+            VariantKind::Klondike => format!(
+                "variant={}&draw={}&passes={}&scoring={}",
+                self.variant, self.draw, self.passes, self.scoring
+            ),
+            VariantKind::Spider(suits) => {
+                format!("variant={}&suits={suits}", self.variant)
+            }
+            VariantKind::FreeCell => format!("variant={}", self.variant),
+        }
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.variant {
+            VariantKind::Spider(SpiderSuits::One) => {
+                return write!(f, "Spider · 1 suit")
+            }
+            VariantKind::Spider(suits) => {
+                return write!(f, "Spider · {suits} suits")
+            }
+            VariantKind::FreeCell => return write!(f, "FreeCell"),
+            VariantKind::Klondike => {}
+        }
+
+        let passes = match self.passes {
+            PassLimit::Unlimited => "unlimited passes",
+            PassLimit::Three => "3 passes",
+            PassLimit::One => "1 pass",
+        };
+        write!(
+            f,
+            "Klondike · Draw {} · {passes} · {}",
+            self.draw, self.scoring
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use crate::game::{DrawMode, Move};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Outcome {
+    pub flipped: bool,
+    pub recycled: bool,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum Scoring {
+    #[default]
+    Standard,
+    Vegas,
+}
+
+impl Scoring {
+    pub fn initial(self) -> i32 {
+        match self {
+            Scoring::Standard => 0,
+            Scoring::Vegas => -52,
+        }
+    }
+
+    fn points(self, m: Move, outcome: Outcome, draw: DrawMode) -> i32 {
+        use Move::*;
+        match self {
+            Scoring::Standard => {
+                let points = match m {
+                    WasteToPile { .. } => 5,
+                    WasteToFoundation { .. } | PileToFoundation { .. } => {
+                        10
+                    }
+                    FoundationToPile { .. } => -15,
+                    Draw if outcome.recycled => match draw {
+                        DrawMode::One => -100,
+                        DrawMode::Three => -20,
+                    },
+                    _ => 0,
+                };
+                points + if outcome.flipped { 5 } else { 0 }
+            }
+            Scoring::Vegas => match m {
+                WasteToFoundation { .. } | PileToFoundation { .. } => 5,
+                FoundationToPile { .. } => -5,
+                _ => 0,
+            },
+        }
+    }
+
+    pub fn score(
+        self,
+        score: i32,
+        m: Move,
+        outcome: Outcome,
+        draw: DrawMode,
+    ) -> i32 {
+        let score = score + self.points(m, outcome, draw);
+        match self {
+            Scoring::Standard => score.max(0),
+            Scoring::Vegas => score,
+        }
+    }
+
+    pub fn format(self, score: i32) -> String {
+        match self {
+            Scoring::Standard => score.to_string(),
+            Scoring::Vegas if score < 0 => format!("-${}", -score),
+            Scoring::Vegas => format!("${score}"),
+        }
+    }
+}
+
+impl fmt::Display for Scoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scoring::Standard => write!(f, "standard"),
+            Scoring::Vegas => write!(f, "vegas"),
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Scoring::Standard),
+            "vegas" => Ok(Scoring::Vegas),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLIPPED: Outcome = Outcome {
+        flipped: true,
+        recycled: false,
+    };
+    const RECYCLED: Outcome = Outcome {
+        flipped: false,
+        recycled: true,
+    };
+
+    fn score(
+        scoring: Scoring,
+        score: i32,
+        m: Move,
+        outcome: Outcome,
+    ) -> i32 {
+        scoring.score(score, m, outcome, DrawMode::One)
+    }
+
+    #[test]
+    fn scores_standard_moves() {
+        let standard =
+            |m, outcome| score(Scoring::Standard, 100, m, outcome);
+        let none = Outcome::default();
+        assert_eq!(Scoring::Standard.initial(), 0);
+        assert_eq!(standard(Move::WasteToPile { to: 0 }, none), 105);
+        assert_eq!(standard(Move::WasteToFoundation { to: 0 }, none), 110);
+        let m = Move::PileToFoundation { from: 0, to: 0 };
+        assert_eq!(standard(m, FLIPPED), 115);
+        assert_eq!(
+            standard(Move::FoundationToPile { from: 0, to: 0 }, none),
+            85
+        );
+        assert_eq!(standard(Move::Draw, none), 100);
+    }
+
+    #[test]
+    fn charges_for_recycling_the_waste() {
+        let recycle = |draw| {
+            Scoring::Standard.score(500, Move::Draw, RECYCLED, draw)
+        };
+        assert_eq!(recycle(DrawMode::One), 400);
+        assert_eq!(recycle(DrawMode::Three), 480);
+        // Standard scores never go negative
+        assert_eq!(score(Scoring::Standard, 50, Move::Draw, RECYCLED), 0);
+    }
+
+    #[test]
+    fn pays_out_vegas_style() {
+        let vegas = |from, m| score(Scoring::Vegas, from, m, FLIPPED);
+        assert_eq!(Scoring::Vegas.initial(), -52);
+        let m = Move::PileToFoundation { from: 0, to: 0 };
+        assert_eq!(vegas(-52, m), -47);
+        assert_eq!(vegas(-47, Move::WasteToFoundation { to: 0 }), -42);
+        assert_eq!(
+            vegas(-42, Move::FoundationToPile { from: 0, to: 0 }),
+            -47
+        );
+        // Nothing else pays, and nothing is charged for passes
+        assert_eq!(vegas(-47, Move::WasteToPile { to: 0 }), -47);
+        assert_eq!(score(Scoring::Vegas, -47, Move::Draw, RECYCLED), -47);
+    }
+
+    #[test]
+    fn formats_vegas_scores_as_money() {
+        assert_eq!(Scoring::Standard.format(-5), "-5");
+        assert_eq!(Scoring::Standard.format(120), "120");
+        assert_eq!(Scoring::Vegas.format(-52), "-$52");
+        assert_eq!(Scoring::Vegas.format(0), "$0");
+        assert_eq!(Scoring::Vegas.format(208), "$208");
+    }
+}
@@ -1,198 +1,601 @@
-use crate::game::Card;
+use crate::game::{
+    Card, GameRecord, GameState, History, Move, MoveError, Rules,
+};
 use leptos::*;
 use leptos_dom::log;
-use rand::prelude::SliceRandom;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Selection {
     Pile(usize, usize),
     Foundation(usize),
+    Cell(usize),
     Deck,
     Waste,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Solitaire {
-    pub deck: RwSignal<Vec<Card>>,
-    pub waste: RwSignal<Vec<Card>>,
-    pub piles: [RwSignal<Vec<Card>>; 7],
-    pub foundations: [RwSignal<Vec<Card>>; 4],
-    pub selected: RwSignal<Option<Selection>>,
+impl Selection {
+    pub fn dom_id(&self) -> String {
+        match self {
+            Selection::Pile(idx, n) => format!("pile-{idx}-{n}"),
+            Selection::Foundation(idx) => format!("foundation-{idx}"),
+            Selection::Cell(idx) => format!("cell-{idx}"),
+            Selection::Deck => "deck".to_owned(),
+            Selection::Waste => "waste".to_owned(),
+        }
+    }
+
+    pub fn from_dom_id(id: &str) -> Option<Self> {
+        let mut parts = id.split('-');
+        match (parts.next()?, parts.next(), parts.next()) {
+            ("pile", Some(idx), Some(n)) => {
+                Some(Selection::Pile(idx.parse().ok()?, n.parse().ok()?))
+            }
+            ("foundation", Some(idx), None) => {
+                Some(Selection::Foundation(idx.parse().ok()?))
+            }
+            ("cell", Some(idx), None) => {
+                Some(Selection::Cell(idx.parse().ok()?))
+            }
+            ("deck", None, None) => Some(Selection::Deck),
+            ("waste", None, None) => Some(Selection::Waste),
+            _ => None,
+        }
+    }
 }
 
-fn move_card(
-    from: RwSignal<Vec<Card>>,
-    n_from: usize,
-    to: RwSignal<Vec<Card>>,
-) {
-    let card: Vec<_> = from
-        .try_update(|from| from.drain(from.len() - n_from..).collect())
-        .expect("for signal to still be valid");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
 
-    to.update(|to| to.extend(card));
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub state: GameState,
+    pub history: History,
+    pub winnable: bool,
+}
+
+const AUTO_COMPLETE_DELAY: Duration = Duration::from_millis(150);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Solitaire {
+    state: RwSignal<GameState>,
+    history: RwSignal<History>,
+    pub deck: Memo<Vec<Card>>,
+    pub waste: Memo<Vec<Card>>,
+    piles: StoredValue<Vec<Memo<Vec<Card>>>>,
+    foundations: StoredValue<Vec<Memo<Vec<Card>>>>,
+    cells: StoredValue<Vec<Memo<Vec<Card>>>>,
+    pub selected: RwSignal<Option<Selection>>,
+    pub focus: RwSignal<Option<Selection>>,
+    pub dragging: RwSignal<Option<Selection>>,
+    pub drop_target: RwSignal<Option<Selection>>,
+    pub error: RwSignal<Option<MoveError>>,
+    pub announcement: RwSignal<String>,
+    pub hint: RwSignal<Option<(Selection, Selection)>>,
+    pub is_won: Memo<bool>,
+    pub can_auto_complete: Memo<bool>,
 }
 
 impl Solitaire {
-    pub fn new(mut cards: Vec<Card>) -> Self {
+    pub fn new(state: GameState) -> Self {
         log!("Creating new solitaire game");
-        cards.shuffle(&mut rand::thread_rng());
-
-        let mut drain_into_signal =
-            |r| create_rw_signal(cards.drain(r).collect());
-        let piles = [
-            drain_into_signal(0..1),
-            drain_into_signal(0..2),
-            drain_into_signal(0..3),
-            drain_into_signal(0..4),
-            drain_into_signal(0..5),
-            drain_into_signal(0..6),
-            drain_into_signal(0..7),
-        ];
+        let (piles, foundations, cells) = (
+            state.piles.len(),
+            state.foundations.len(),
+            state.cells.len(),
+        );
+        let state = create_rw_signal(state);
 
         Self {
-            deck: create_rw_signal(cards.to_vec()),
-            waste: create_rw_signal(Vec::new()),
-            piles,
-            foundations: std::array::from_fn(|_| {
-                create_rw_signal(Vec::new())
-            }),
+            state,
+            history: create_rw_signal(History::default()),
+            deck: create_memo(move |_| state.with(|s| s.deck.clone())),
+            waste: create_memo(move |_| state.with(|s| s.waste.clone())),
+            piles: store_value(
+                (0..piles)
+                    .map(|idx| {
+                        create_memo(move |_| {
+                            state.with(|s| s.piles[idx].clone())
+                        })
+                    })
+                    .collect(),
+            ),
+            foundations: store_value(
+                (0..foundations)
+                    .map(|idx| {
+                        create_memo(move |_| {
+                            state.with(|s| s.foundations[idx].clone())
+                        })
+                    })
+                    .collect(),
+            ),
+            cells: store_value(
+                (0..cells)
+                    .map(|idx| {
+                        create_memo(move |_| {
+                            state.with(|s| s.cells[idx].clone())
+                        })
+                    })
+                    .collect(),
+            ),
             selected: create_rw_signal(None),
+            focus: create_rw_signal(None),
+            dragging: create_rw_signal(None),
+            drop_target: create_rw_signal(None),
+            error: create_rw_signal(None),
+            announcement: create_rw_signal(String::new()),
+            hint: create_rw_signal(None),
+            is_won: create_memo(move |_| state.with(GameState::is_won)),
+            can_auto_complete: create_memo(move |_| {
+                state.with(GameState::is_trivially_winnable)
+            }),
+        }
+    }
+
+    pub fn restore(saved: SavedGame) -> Self {
+        let game = Self::new(saved.state);
+        game.history.set(saved.history);
+        game
+    }
+
+    pub fn snapshot(&self, winnable: bool) -> SavedGame {
+        SavedGame {
+            state: self.state.get(),
+            history: self.history.get(),
+            winnable,
+        }
+    }
+
+    pub fn record(&self) -> GameRecord {
+        self.state.with_untracked(GameRecord::new)
+    }
+
+    pub fn piles(&self) -> Vec<Memo<Vec<Card>>> {
+        self.piles.get_value()
+    }
+
+    pub fn foundations(&self) -> Vec<Memo<Vec<Card>>> {
+        self.foundations.get_value()
+    }
+
+    pub fn cells(&self) -> Vec<Memo<Vec<Card>>> {
+        self.cells.get_value()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.state.with_untracked(|state| state.seed)
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.state.with_untracked(|state| state.rules)
+    }
+
+    pub fn score(&self) -> i32 {
+        self.state.with(|state| state.score)
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.state.with(|state| state.moves)
+    }
+
+    pub fn elapsed(&self) -> u64 {
+        self.state.with(|state| state.elapsed)
+    }
+
+    pub fn tick(&self) {
+        let paused = document().hidden()
+            || !self.state.with_untracked(GameState::is_clock_running);
+        if !paused {
+            self.state.update(GameState::tick);
         }
     }
 
+    pub fn is_stock_exhausted(&self) -> bool {
+        self.state.with(GameState::is_stock_exhausted)
+    }
+
     pub fn clear_selection(&self) {
         self.selected.set(None);
     }
 
-    fn is_valid_move_to_pile(&self, from: &[Card], to: &[Card]) -> bool {
-        if from.is_empty() {
-            return false;
-        }
-        if to.is_empty() {
-            return from.last().unwrap().value == 13;
+    fn to_move(from: Selection, to: Selection) -> Result<Move, MoveError> {
+        use Selection::*;
+        match (from, to) {
+            (Pile(from, n), Pile(to, _)) => {
+                Ok(Move::PileToPile { from, n, to })
+            }
+            (Pile(_, 0), Foundation(_)) => Err(MoveError::EmptySource),
+            (Pile(from, 1), Foundation(to)) => {
+                Ok(Move::PileToFoundation { from, to })
+            }
+            (Pile(_, _), Foundation(_)) => Err(MoveError::NotTopCard),
+            (Foundation(from), Pile(to, _)) => {
+                Ok(Move::FoundationToPile { from, to })
+            }
+            (Waste, Pile(to, _)) => Ok(Move::WasteToPile { to }),
+            (Waste, Foundation(to)) => Ok(Move::WasteToFoundation { to }),
+            (Pile(_, 0), Cell(_)) => Err(MoveError::EmptySource),
+            (Pile(from, 1), Cell(to)) => Ok(Move::PileToCell { from, to }),
+            (Pile(_, _), Cell(_)) => Err(MoveError::NotTopCard),
+            (Cell(from), Pile(to, _)) => Ok(Move::CellToPile { from, to }),
+            (Cell(from), Foundation(to)) => {
+                Ok(Move::CellToFoundation { from, to })
+            }
+            _ => Err(MoveError::Unsupported),
         }
-        let from_card = from.last().unwrap();
-        let to_card = to.last().unwrap();
+    }
 
-        from_card.value + 1 == to_card.value
-            && from_card.color() != to_card.color()
+    fn set_state(&self, next: GameState) {
+        // Keep the cursor on a card that still exists after the move
+        if let Some(Selection::Pile(idx, n)) = self.focus.get_untracked() {
+            let faceup = next.piles[idx]
+                .iter()
+                .rev()
+                .take_while(|card| card.is_faceup())
+                .count();
+            if n == 0 || n > faceup {
+                self.focus.set(Some(Self::pile_focus(&next, idx)));
+            }
+        }
+        self.hint.set(None);
+        self.state.set(next);
     }
 
-    fn is_valid_move_to_foundation(
-        &self,
-        from: &[Card],
-        to: &[Card],
-    ) -> bool {
-        if from.is_empty() {
-            return false;
+    fn apply(&self, m: Move) -> Result<(), MoveError> {
+        let result = self.state.with_untracked(|state| state.apply(m));
+        if let Err(err) = result {
+            log!("{:?}: {}", m, err);
         }
-        if to.is_empty() {
-            return from.last().unwrap().value == 1;
+        self.error.set(result.as_ref().err().copied());
+        let next = result?;
+
+        let previous = self.state.get_untracked();
+        self.announcement.set(previous.describe(m, &next));
+        self.history.update(|history| history.record(previous, m));
+        self.set_state(next);
+
+        Ok(())
+    }
+
+    pub fn auto_complete(&self) {
+        let game = *self;
+        // The board may have been unmounted since the last step, taking its
+        // signals with it, in which case there is nothing left to finish
+        let Some(m) = self
+            .state
+            .try_with_untracked(|state| {
+                state
+                    .is_trivially_winnable()
+                    .then(|| state.next_auto_complete_move())
+                    .flatten()
+            })
+            .flatten()
+        else {
+            return;
+        };
+
+        if self.apply(m).is_ok() {
+            set_timeout(move || game.auto_complete(), AUTO_COMPLETE_DELAY);
         }
-        let from_card = from.last().unwrap();
-        let to_card = to.last().unwrap();
+    }
+
+    pub fn show_hint(&self) {
+        self.clear_selection();
+        let hint = self.state.with_untracked(|state| {
+            let m = state.hints().into_iter().next()?;
+            Some(Self::hint_selections(state, m))
+        });
+
+        self.error.set(hint.is_none().then_some(MoveError::NoHint));
+        self.hint.set(hint);
+    }
 
-        from_card.value - 1 == to_card.value
-            && from_card.suit == to_card.suit
+    pub fn is_hinted(&self, s: Selection) -> bool {
+        self.hint.with(|hint| {
+            hint.is_some_and(|(from, to)| from == s || to == s)
+        })
     }
 
-    pub fn play(&mut self, s: Selection) {
+    fn hint_selections(
+        state: &GameState,
+        m: Move,
+    ) -> (Selection, Selection) {
         use Selection::*;
-        log!("Playing {:?}", s);
-        match (self.selected.get(), s) {
-            (None, _) => {
-                self.selected.set(Some(s));
-                return;
-            }
-            (Some(Pile(from, from_card)), Pile(to, _)) => {
-                let source = self.piles[from];
-                let destination = self.piles[to];
-                let valid = self.is_valid_move_to_pile(
-                    &source()[..=source().len() - from_card],
-                    &destination(),
-                );
-                if valid {
-                    move_card(source, from_card, destination);
-                }
+        let pile_top =
+            |idx: usize| Pile(idx, state.piles[idx].len().min(1));
+        match m {
+            Move::PileToPile { from, n, to } => {
+                (Pile(from, n), pile_top(to))
+            }
+            Move::PileToFoundation { from, to } => {
+                (Pile(from, 1), Foundation(to))
+            }
+            Move::FoundationToPile { from, to } => {
+                (Foundation(from), pile_top(to))
             }
-            (Some(Pile(from, _)), Foundation(destination)) => {
-                let source = self.piles[from];
-                let destination = self.foundations[destination];
-                let valid = self.is_valid_move_to_foundation(
-                    &source(),
-                    &destination(),
-                );
+            Move::WasteToPile { to } => (Waste, pile_top(to)),
+            Move::WasteToFoundation { to } => (Waste, Foundation(to)),
+            Move::PileToCell { from, to } => (Pile(from, 1), Cell(to)),
+            Move::CellToPile { from, to } => (Cell(from), pile_top(to)),
+            Move::CellToFoundation { from, to } => {
+                (Cell(from), Foundation(to))
+            }
+            Move::Draw => (Deck, Deck),
+        }
+    }
+
+    pub fn is_focused(&self, s: Selection) -> bool {
+        self.focus.with(|focus| *focus == Some(s))
+    }
+
+    pub fn move_focus(&self, direction: Direction) {
+        let focus = self.focus.get_untracked();
+        let next = self.state.with_untracked(|state| match focus {
+            Some(focus) => Self::next_focus(state, focus, direction),
+            None => Self::pile_focus(state, 0),
+        });
+        self.focus.set(Some(next));
+    }
+
+    fn pile_focus(state: &GameState, idx: usize) -> Selection {
+        Selection::Pile(idx, state.piles[idx].len().min(1))
+    }
 
-                if valid {
-                    move_card(source, 1, destination);
+    // The cursor walks the top row (foundations, then the stock and waste or
+    // the free cells) and the face-up cards of each pile, with pile columns
+    // lining up under top row slots
+    fn next_focus(
+        state: &GameState,
+        focus: Selection,
+        direction: Direction,
+    ) -> Selection {
+        use Direction::*;
+        use Selection::*;
+        let mut top_row: Vec<_> =
+            (0..state.foundations.len()).map(Foundation).collect();
+        if state.rules.has_stock() {
+            top_row.push(Deck);
+        }
+        if state.rules.has_waste() {
+            top_row.push(Waste);
+        }
+        top_row.extend((0..state.cells.len()).map(Cell));
+        let piles = state.piles.len();
+        let faceup = |idx: usize| {
+            state.piles[idx]
+                .iter()
+                .rev()
+                .take_while(|card| card.is_faceup())
+                .count()
+        };
+
+        match (focus, direction) {
+            (Pile(idx, _), Left) => {
+                Self::pile_focus(state, (idx + piles - 1) % piles)
+            }
+            (Pile(idx, _), Right) => {
+                Self::pile_focus(state, (idx + 1) % piles)
+            }
+            (Pile(idx, n), Up) if n < faceup(idx) => Pile(idx, n + 1),
+            (Pile(idx, _), Up) => top_row[idx.min(top_row.len() - 1)],
+            (Pile(idx, n), Down) if n > 1 => {
+                Pile(idx, (n - 1).min(faceup(idx).max(1)))
+            }
+            (Pile(idx, _), Down) => Self::pile_focus(state, idx),
+            (top, direction) => {
+                let slot =
+                    top_row.iter().position(|&s| s == top).unwrap_or(0);
+                match direction {
+                    Left => {
+                        top_row[(slot + top_row.len() - 1) % top_row.len()]
+                    }
+                    Right => top_row[(slot + 1) % top_row.len()],
+                    Down => Self::pile_focus(state, slot.min(piles - 1)),
+                    Up => top,
                 }
             }
-            (Some(Foundation(source)), Pile(destination, _)) => {
-                let source = self.foundations[source];
-                let destination = self.piles[destination];
-                let valid =
-                    self.is_valid_move_to_pile(&source(), &destination());
+        }
+    }
+
+    pub fn play_focused(&self) -> Result<(), MoveError> {
+        match self.focus.get_untracked() {
+            Some(Selection::Deck) => self.draw(),
+            Some(s) => self.play(s),
+            None => Ok(()),
+        }
+    }
+
+    pub fn send_focused_to_foundation(&self) -> Result<(), MoveError> {
+        match self.focus.get_untracked() {
+            Some(s) => self.send_to_foundation(s),
+            None => Ok(()),
+        }
+    }
+
+    // Changes with every move, undo and redo, but not with the clock
+    pub fn progress(&self) -> (u32, usize) {
+        (self.moves(), self.history.with(History::len))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.with(History::can_undo)
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.with(History::can_redo)
+    }
+
+    pub fn undo(&self) {
+        if self.step_history(History::undo) {
+            self.announcement.set("Undid the last move".to_owned());
+        }
+    }
+
+    pub fn redo(&self) {
+        if self.step_history(History::redo) {
+            self.announcement.set("Redid the move".to_owned());
+        }
+    }
 
-                if valid {
-                    move_card(source, 1, destination);
+    fn step_history(
+        &self,
+        step: fn(&mut History, GameState) -> Option<GameState>,
+    ) -> bool {
+        let current = self.state.get_untracked();
+        let (moves, elapsed) = (current.moves, current.elapsed);
+        let next = self
+            .history
+            .try_update(|history| step(history, current))
+            .flatten();
+
+        // Undo rewinds the board, not the clock or the move count
+        if let Some(mut next) = next {
+            next.moves = moves;
+            next.elapsed = elapsed;
+            self.clear_selection();
+            self.error.set(None);
+            self.set_state(next);
+            return true;
+        }
+        false
+    }
+
+    pub fn play(&self, s: Selection) -> Result<(), MoveError> {
+        log!("Playing {:?}", s);
+        let Some(from) = self.selected.get_untracked() else {
+            self.error.set(None);
+            self.selected.set(Some(s));
+            self.announcement.set(self.describe_selection(s));
+            return Ok(());
+        };
+
+        self.selected.set(None);
+        Self::to_move(from, s)
+            .inspect_err(|err| self.error.set(Some(*err)))
+            .and_then(|m| self.apply(m))
+    }
+
+    fn describe_selection(&self, s: Selection) -> String {
+        let name = self.state.with_untracked(|state| {
+            let top = |cards: &[Card]| cards.last().map(Card::name);
+            match s {
+                Selection::Pile(idx, n) => {
+                    let pile = &state.piles[idx];
+                    pile.len()
+                        .checked_sub(n)
+                        .and_then(|i| pile.get(i))
+                        .map(Card::name)
                 }
+                Selection::Foundation(idx) => top(&state.foundations[idx]),
+                Selection::Cell(idx) => top(&state.cells[idx]),
+                Selection::Waste => top(&state.waste),
+                Selection::Deck => Some("the stock".to_owned()),
             }
-            (Some(Waste), Pile(destination, _)) => {
-                let source = self.waste;
-                let destination = self.piles[destination];
-                let valid =
-                    self.is_valid_move_to_pile(&source(), &destination());
+        });
+        match name {
+            Some(name) => format!("Selected {name}"),
+            None => "Selected an empty spot".to_owned(),
+        }
+    }
 
-                if valid {
-                    move_card(source, 1, destination);
-                }
+    pub fn start_drag(&self, s: Selection) {
+        self.clear_selection();
+        self.error.set(None);
+        self.dragging.set(Some(s));
+    }
+
+    pub fn end_drag(&self) {
+        self.dragging.set(None);
+        self.drop_target.set(None);
+    }
+
+    // A pile drag carries every card above the one picked up
+    pub fn is_dragged(&self, s: Selection) -> bool {
+        self.dragging.with(|dragging| match (*dragging, s) {
+            (Some(Selection::Pile(from, n)), Selection::Pile(idx, m)) => {
+                from == idx && (1..=n).contains(&m)
             }
-            (Some(Waste), Foundation(destination)) => {
-                let source = self.waste;
-                let destination = self.foundations[destination];
-                let valid = self.is_valid_move_to_foundation(
-                    &source(),
-                    &destination(),
-                );
+            (dragging, s) => dragging == Some(s),
+        })
+    }
 
-                if valid {
-                    move_card(source, 1, destination);
-                }
+    pub fn can_drop(&self, to: Selection) -> bool {
+        let Some(from) = self.dragging.get() else {
+            return false;
+        };
+        Self::to_move(from, to)
+            .is_ok_and(|m| self.state.with(|state| state.check(m).is_ok()))
+    }
+
+    pub fn is_drop_target(&self, to: Selection) -> bool {
+        self.drop_target.with(|target| *target == Some(to))
+            && self.can_drop(to)
+    }
+
+    pub fn drop(
+        &self,
+        from: Selection,
+        to: Selection,
+    ) -> Result<(), MoveError> {
+        log!("Dropping {:?} on {:?}", from, to);
+        self.end_drag();
+        Self::to_move(from, to)
+            .inspect_err(|err| self.error.set(Some(*err)))
+            .and_then(|m| self.apply(m))
+    }
+
+    pub fn send_to_foundation(
+        &self,
+        s: Selection,
+    ) -> Result<(), MoveError> {
+        log!("Sending {:?} to foundation", s);
+        self.clear_selection();
+
+        let m = self.state.with_untracked(|state| match s {
+            Selection::Pile(from, 1) => {
+                let card = state
+                    .pile(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                let to = state
+                    .foundation_for(card)
+                    .ok_or(MoveError::NoFoundation)?;
+                Ok(Move::PileToFoundation { from, to })
             }
-            _ => {
-                log!("Invalid move");
+            Selection::Pile(_, 0) => Err(MoveError::EmptySource),
+            Selection::Pile(_, _) => Err(MoveError::NotTopCard),
+            Selection::Waste => {
+                let card =
+                    state.waste.last().ok_or(MoveError::EmptySource)?;
+                let to = state
+                    .foundation_for(card)
+                    .ok_or(MoveError::NoFoundation)?;
+                Ok(Move::WasteToFoundation { to })
             }
-        }
+            Selection::Cell(from) => {
+                let card = state
+                    .cell(from)?
+                    .last()
+                    .ok_or(MoveError::EmptySource)?;
+                let to = state
+                    .foundation_for(card)
+                    .ok_or(MoveError::NoFoundation)?;
+                Ok(Move::CellToFoundation { from, to })
+            }
+            _ => Err(MoveError::Unsupported),
+        });
 
-        self.selected.set(None);
+        m.inspect_err(|err| self.error.set(Some(*err)))
+            .and_then(|m| self.apply(m))
     }
 
-    pub fn draw(&mut self) {
-        let deck = self.deck;
-        let waste = self.waste;
-
-        match self.deck.get().last() {
-            Some(card) => {
-                deck.update(|d| {
-                    d.pop();
-                });
-                waste.update(|w| {
-                    let mut card = card.to_owned();
-                    card.flip();
-                    w.push(card);
-                });
-            }
-            None => {
-                let mut cur_waste = waste();
-                cur_waste.reverse();
-                deck.update(|d| {
-                    d.extend(cur_waste);
-                });
-                waste.update(|w| {
-                    w.clear();
-                });
-            }
-        }
+    pub fn draw(&self) -> Result<(), MoveError> {
+        self.apply(Move::Draw)
     }
 }
@@ -0,0 +1,201 @@
+use crate::game::{Card, GameState, Move, PassLimit};
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    Winnable(Vec<Move>),
+    Unwinnable,
+    Unknown,
+}
+
+// The search skips moves it judges unhelpful, so running out of moves only
+// proves a deal unwinnable if nothing was skipped along the way
+pub fn solve(state: &GameState, budget: usize) -> Solution {
+    let mut visited = HashSet::from([key(state)]);
+    let mut path = Vec::new();
+    let mut pruned = false;
+    let mut stack = vec![(state.clone(), candidates(state, &mut pruned))];
+    let mut nodes = 0;
+
+    while let Some((state, moves)) = stack.last_mut() {
+        if state.is_trivially_winnable() || state.is_won() {
+            path.extend(finish(state));
+            return Solution::Winnable(path);
+        }
+        let Some(m) = moves.pop() else {
+            stack.pop();
+            path.pop();
+            continue;
+        };
+
+        nodes += 1;
+        if nodes > budget {
+            return Solution::Unknown;
+        }
+
+        let next = state.apply(m).expect("candidate moves are legal");
+        if !visited.insert(key(&next)) {
+            continue;
+        }
+        let moves = candidates(&next, &mut pruned);
+        path.push(m);
+        stack.push((next, moves));
+    }
+
+    match pruned {
+        true => Solution::Unknown,
+        false => Solution::Unwinnable,
+    }
+}
+
+fn finish(state: &GameState) -> Vec<Move> {
+    let mut state = state.clone();
+    let mut moves = Vec::new();
+    while let Some(m) = state.next_auto_complete_move() {
+        state = state.apply(m).expect("auto-complete moves are legal");
+        moves.push(m);
+    }
+
+    moves
+}
+
+fn is_safe_for_foundation(state: &GameState, card: &Card) -> bool {
+    card.value <= 2
+        || state
+            .foundations
+            .iter()
+            .filter(|foundation| {
+                foundation
+                    .first()
+                    .is_some_and(|ace| ace.color() != card.color())
+            })
+            .filter(|foundation| {
+                foundation.len() + 1 >= card.value as usize
+            })
+            .count()
+            == 2
+}
+
+// Hints worth trying, best first. Both shortcuts below are heuristics (a
+// waste card played early changes which cards a draw-three deal turns up),
+// so `pruned` is set whenever one of them throws a move away.
+fn candidates(state: &GameState, pruned: &mut bool) -> Vec<Move> {
+    let mut moves = state.hints();
+    let hints = moves.len();
+    let safe = moves.iter().copied().find(|&m| match m {
+        Move::PileToFoundation { from, .. } => state.piles[from]
+            .last()
+            .is_some_and(|card| is_safe_for_foundation(state, card)),
+        Move::WasteToFoundation { .. } => state
+            .waste
+            .last()
+            .is_some_and(|card| is_safe_for_foundation(state, card)),
+        _ => false,
+    });
+    if let Some(m) = safe {
+        *pruned |= hints > 1;
+        return vec![m];
+    }
+
+    // Shuffling a run between two face-up cards only helps if it frees
+    // the card underneath for a foundation
+    moves.retain(|&m| match m {
+        Move::PileToPile { from, n, .. } => {
+            let pile = &state.piles[from];
+            let rest = pile.len() - n;
+            rest == 0
+                || !pile[rest - 1].is_faceup()
+                || state.foundation_for(&pile[rest - 1]).is_some()
+        }
+        _ => true,
+    });
+    *pruned |= moves.len() < hints;
+    // Best moves last, so they are popped first
+    moves.reverse();
+    moves
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Tableau order doesn't change what's reachable, so piles are hashed as
+// a set
+fn key(state: &GameState) -> u64 {
+    let mut piles: Vec<_> = state.piles.iter().map(hash_of).collect();
+    piles.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    state.deck.hash(&mut hasher);
+    state.waste.hash(&mut hasher);
+    state.foundations.hash(&mut hasher);
+    piles.hash(&mut hasher);
+    if state.rules.passes != PassLimit::Unlimited {
+        state.recycles.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        state::tests::{board, card, down, full_deck},
+        DrawMode, Rules,
+    };
+
+    const BUDGET: usize = 100_000;
+
+    fn deal(seed: u64, draw: DrawMode) -> GameState {
+        let rules = Rules {
+            draw,
+            ..Default::default()
+        };
+        GameState::new(full_deck(), Some(seed), rules)
+    }
+
+    fn assert_wins(state: GameState) {
+        let Solution::Winnable(path) = solve(&state, BUDGET) else {
+            panic!("deal {} should be winnable", state.seed);
+        };
+        let end = path.iter().try_fold(state, |state, &m| state.apply(m));
+        assert!(end.unwrap().is_won());
+    }
+
+    #[test]
+    fn solves_a_draw_one_deal() {
+        assert_wins(deal(1, DrawMode::One));
+    }
+
+    #[test]
+    fn solves_a_draw_three_deal() {
+        assert_wins(deal(1, DrawMode::Three));
+    }
+
+    #[test]
+    fn gives_up_when_out_of_budget() {
+        assert_eq!(solve(&deal(1, DrawMode::One), 0), Solution::Unknown);
+    }
+
+    #[test]
+    fn proves_a_dead_end_unwinnable() {
+        let mut state = board(Rules::default());
+        state.piles[0] = vec![down("AH"), card("2H")];
+        assert_eq!(solve(&state, BUDGET), Solution::Unwinnable);
+    }
+
+    #[test]
+    fn hedges_when_moves_were_skipped() {
+        // The only legal move shuffles the eight of spades between two
+        // face-up nines, which the search skips
+        let mut state = board(Rules::default());
+        state.piles[0] = vec![down("AH"), card("9D"), card("8S")];
+        state.piles[1] = vec![card("9H")];
+        assert_eq!(solve(&state, BUDGET), Solution::Unknown);
+    }
+}
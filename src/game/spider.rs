@@ -0,0 +1,260 @@
+use crate::game::{
+    Card, GameState, Layout, Move, MoveError, Outcome, Rules, Variant,
+    VariantKind,
+};
+
+// Spider: two decks dealt into ten piles, played down regardless of suit,
+// but only runs of a single suit move together. A full king-to-ace run of
+// one suit is cleared off to the foundations as soon as it is built.
+
+const DECK_SIZE: usize = 104;
+const PILES: usize = 10;
+const FOUNDATIONS: usize = 8;
+const RUN_LENGTH: usize = 13;
+const INITIAL_SCORE: i32 = 500;
+const RUN_BONUS: i32 = 100;
+
+pub struct Spider;
+
+fn is_run(cards: &[Card]) -> bool {
+    cards.windows(2).all(|pair| {
+        pair[0].suit == pair[1].suit && pair[0].value == pair[1].value + 1
+    })
+}
+
+impl Spider {
+    // Clears finished runs off the tableau, returning how many there were
+    fn complete_runs(state: &mut GameState) -> i32 {
+        let mut completed = 0;
+        for idx in 0..state.piles.len() {
+            let pile = &state.piles[idx];
+            let Some(start) = pile.len().checked_sub(RUN_LENGTH) else {
+                continue;
+            };
+            let run = &pile[start..];
+            let complete = run[0].value == 13
+                && run.iter().all(Card::is_faceup)
+                && is_run(run);
+            let Some(to) =
+                state.foundations.iter().position(Vec::is_empty)
+            else {
+                break;
+            };
+            if !complete {
+                continue;
+            }
+
+            let run: Vec<_> = state.piles[idx].drain(start..).collect();
+            state.foundations[to].extend(run);
+            if let Some(card) = state.piles[idx].last_mut() {
+                card.flip();
+            }
+            completed += 1;
+        }
+
+        completed
+    }
+}
+
+impl Variant for Spider {
+    fn deck(&self, cards: Vec<Card>, rules: &Rules) -> Vec<Card> {
+        let VariantKind::Spider(suits) = rules.variant else {
+            return cards;
+        };
+        let cards: Vec<_> = cards
+            .into_iter()
+            .filter(|card| suits.suits().contains(&card.suit))
+            .collect();
+        let copies = DECK_SIZE / cards.len();
+        (0..copies)
+            .flat_map(|copy| {
+                cards
+                    .iter()
+                    .map(move |card| card.clone().with_copy(copy as u8))
+            })
+            .collect()
+    }
+
+    // The first four piles get six cards and the rest get five, leaving
+    // five rows' worth in the stock
+    fn layout(&self, cards: &mut Vec<Card>) -> Layout {
+        Layout {
+            piles: (0..PILES)
+                .map(|i| {
+                    cards.drain(0..if i < 4 { 6 } else { 5 }).collect()
+                })
+                .collect(),
+            foundations: vec![Vec::new(); FOUNDATIONS],
+            cells: Vec::new(),
+        }
+    }
+
+    fn initial_score(&self, _rules: &Rules) -> i32 {
+        INITIAL_SCORE
+    }
+
+    fn check(&self, state: &GameState, m: Move) -> Result<(), MoveError> {
+        match m {
+            Move::PileToPile { from, n, to } => {
+                let source = state.pile(from)?;
+                let target = state.pile(to)?;
+                if from == to {
+                    return Err(MoveError::SamePile);
+                }
+                if n == 0 || n > source.len() {
+                    return Err(MoveError::EmptySource);
+                }
+                let run = &source[source.len() - n..];
+                if !run.iter().all(Card::is_faceup) {
+                    return Err(MoveError::FaceDown);
+                }
+                if !is_run(run) {
+                    return Err(MoveError::NotARun);
+                }
+                match target.last() {
+                    Some(to_card) if to_card.value != run[0].value + 1 => {
+                        Err(MoveError::WrongRank)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Move::Draw if state.deck.is_empty() => {
+                Err(MoveError::NothingToDraw)
+            }
+            Move::Draw if state.piles.iter().any(Vec::is_empty) => {
+                Err(MoveError::EmptyPile)
+            }
+            Move::Draw => Ok(()),
+            _ => Err(MoveError::Unsupported),
+        }
+    }
+
+    // Drawing deals one face-up card onto every pile
+    fn draw(&self, state: &mut GameState) -> Outcome {
+        for pile in state.piles.iter_mut() {
+            if let Some(mut card) = state.deck.pop() {
+                card.flip();
+                pile.push(card);
+            }
+        }
+
+        Outcome::default()
+    }
+
+    fn settle(
+        &self,
+        previous: &GameState,
+        next: &mut GameState,
+        _m: Move,
+        _outcome: Outcome,
+    ) {
+        let completed = Self::complete_runs(next);
+        next.score = previous.score - 1 + RUN_BONUS * completed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        state::tests::{board, card, cards, down, full_deck, names},
+        SpiderSuits,
+    };
+
+    fn rules(suits: SpiderSuits) -> Rules {
+        Rules {
+            variant: VariantKind::Spider(suits),
+            ..Default::default()
+        }
+    }
+
+    // An empty Spider board to lay out by hand
+    fn spider_board() -> GameState {
+        let mut state = board(rules(SpiderSuits::Four));
+        state.piles = vec![Vec::new(); PILES];
+        state.foundations = vec![Vec::new(); FOUNDATIONS];
+        state.score = INITIAL_SCORE;
+        state
+    }
+
+    #[test]
+    fn deals_two_packs_worth_of_cards() {
+        for (suits, copies) in [
+            (SpiderSuits::One, 8),
+            (SpiderSuits::Two, 4),
+            (SpiderSuits::Four, 2),
+        ] {
+            let state = GameState::new(full_deck(), Some(1), rules(suits));
+            let dealt: usize = state.piles.iter().map(Vec::len).sum();
+            assert_eq!((dealt, state.deck.len()), (54, 50));
+
+            let all = state.piles.iter().flatten().chain(&state.deck);
+            let aces = all
+                .filter(|card| {
+                    card.value == 1 && card.suit == suits.suits()[0]
+                })
+                .count();
+            assert_eq!(aces, copies, "{suits} suits");
+        }
+    }
+
+    #[test]
+    fn moves_only_single_suit_runs() {
+        let mut state = spider_board();
+        state.piles[0] = cards("9S 8H");
+        state.piles[1] = cards("10D");
+        state.piles[2] = cards("9H 8H");
+
+        let stack = |from, n| {
+            Spider.check(&state, Move::PileToPile { from, n, to: 1 })
+        };
+        assert_eq!(stack(0, 2), Err(MoveError::NotARun));
+        assert_eq!(stack(0, 1), Err(MoveError::WrongRank));
+        assert_eq!(stack(2, 2), Ok(()));
+    }
+
+    #[test]
+    fn deals_a_row_only_onto_full_columns() {
+        let mut state = spider_board();
+        assert_eq!(
+            Spider.check(&state, Move::Draw),
+            Err(MoveError::NothingToDraw)
+        );
+
+        state.deck = vec![down("5C"); PILES];
+        assert_eq!(
+            Spider.check(&state, Move::Draw),
+            Err(MoveError::EmptyPile)
+        );
+        for pile in state.piles.iter_mut() {
+            *pile = cards("KD");
+        }
+        assert_eq!(Spider.check(&state, Move::Draw), Ok(()));
+        let state = state.apply(Move::Draw).unwrap();
+        assert!(state.deck.is_empty());
+        assert!(state.piles.iter().all(|pile| pile.len() == 2));
+    }
+
+    #[test]
+    fn clears_a_finished_run() {
+        let mut state = spider_board();
+        let run: Vec<_> = "K Q J 10 9 8 7 6 5 4 3 2"
+            .split_whitespace()
+            .map(|rank| card(&format!("{rank}S")))
+            .collect();
+        state.piles[0] = [vec![down("5C")], run].concat();
+        state.piles[1] = cards("AS");
+
+        let state = state
+            .apply(Move::PileToPile {
+                from: 1,
+                n: 1,
+                to: 0,
+            })
+            .unwrap();
+        assert_eq!(names(&state.piles[0]), names(&cards("5C")));
+        assert!(state.piles[0][0].is_faceup());
+        assert_eq!(state.foundations[0].len(), RUN_LENGTH);
+        assert_eq!(state.score, INITIAL_SCORE - 1 + RUN_BONUS);
+    }
+}
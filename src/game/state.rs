@@ -0,0 +1,548 @@
+use crate::game::{Card, Layout, Outcome, Rules, Variant};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Move {
+    PileToPile { from: usize, n: usize, to: usize },
+    PileToFoundation { from: usize, to: usize },
+    FoundationToPile { from: usize, to: usize },
+    WasteToPile { to: usize },
+    WasteToFoundation { to: usize },
+    PileToCell { from: usize, to: usize },
+    CellToPile { from: usize, to: usize },
+    CellToFoundation { from: usize, to: usize },
+    Draw,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum MoveError {
+    #[error("There is no card to move")]
+    EmptySource,
+    #[error("That card is face down")]
+    FaceDown,
+    #[error("A card can't be moved onto its own pile")]
+    SamePile,
+    #[error("Only the top card can go to a foundation")]
+    NotTopCard,
+    #[error("Only a king can go on an empty pile")]
+    NotKing,
+    #[error("Only an ace can start a foundation")]
+    NotAce,
+    #[error("Cards must alternate colour")]
+    WrongColour,
+    #[error("Foundations must follow suit")]
+    WrongSuit,
+    #[error("Wrong rank for that spot")]
+    WrongRank,
+    #[error("There are no cards left to draw")]
+    NothingToDraw,
+    #[error("No passes through the deck remain")]
+    NoPassesLeft,
+    #[error("No foundation can take that card")]
+    NoFoundation,
+    #[error("Only a run of one suit can move together")]
+    NotARun,
+    #[error("Every pile needs a card before dealing")]
+    EmptyPile,
+    #[error(
+        "Only a descending run of alternating colours can move together"
+    )]
+    NotASequence,
+    #[error(
+        "Not enough free cells and empty piles to move that many cards"
+    )]
+    TooManyCards,
+    #[error("That free cell is already taken")]
+    CellTaken,
+    #[error("There are no useful moves left")]
+    NoHint,
+    #[error("Cards can't move that way")]
+    Unsupported,
+    #[error("That spot isn't on the board")]
+    NoSuchSpot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameState {
+    pub seed: u64,
+    pub rules: Rules,
+    pub deck: Vec<Card>,
+    pub waste: Vec<Card>,
+    pub piles: Vec<Vec<Card>>,
+    pub foundations: Vec<Vec<Card>>,
+    #[serde(default)]
+    pub cells: Vec<Vec<Card>>,
+    pub recycles: u32,
+    pub score: i32,
+    pub moves: u32,
+    pub elapsed: u64,
+}
+
+impl GameState {
+    pub fn new(cards: Vec<Card>, seed: Option<u64>, rules: Rules) -> Self {
+        let variant = rules.variant.variant();
+        let seed = seed
+            .filter(|&seed| variant.is_valid_seed(seed))
+            .unwrap_or_else(|| variant.random_seed());
+        let mut cards = variant.deck(cards, &rules);
+        cards.sort_by_key(Card::sort_key);
+        variant.shuffle(&mut cards, seed);
+
+        let Layout {
+            mut piles,
+            foundations,
+            cells,
+        } = variant.layout(&mut cards);
+        for pile in piles.iter_mut() {
+            if let Some(card) = pile.last_mut() {
+                card.flip();
+            }
+        }
+
+        Self {
+            seed,
+            rules,
+            deck: cards,
+            waste: Vec::new(),
+            piles,
+            foundations,
+            cells,
+            recycles: 0,
+            score: variant.initial_score(&rules),
+            moves: 0,
+            elapsed: 0,
+        }
+    }
+
+    pub fn variant(&self) -> &'static dyn Variant {
+        self.rules.variant.variant()
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.variant().is_won(self)
+    }
+
+    pub fn is_clock_running(&self) -> bool {
+        self.moves > 0 && !self.is_won()
+    }
+
+    pub fn tick(&mut self) {
+        if self.is_clock_running() {
+            self.elapsed += 1;
+        }
+    }
+
+    pub fn is_trivially_winnable(&self) -> bool {
+        self.variant().can_auto_complete(self) && !self.is_won()
+    }
+
+    pub fn foundation_for(&self, card: &Card) -> Option<usize> {
+        self.variant().foundation_for(self, card)
+    }
+
+    pub fn next_auto_complete_move(&self) -> Option<Move> {
+        self.variant().next_auto_complete_move(self)
+    }
+
+    pub fn is_stock_exhausted(&self) -> bool {
+        self.variant().is_stock_exhausted(self)
+    }
+
+    // Moves can come from saved games and other clients, so look spots up
+    // rather than trusting their indices
+    pub fn pile(&self, idx: usize) -> Result<&[Card], MoveError> {
+        self.piles
+            .get(idx)
+            .map(Vec::as_slice)
+            .ok_or(MoveError::NoSuchSpot)
+    }
+
+    pub fn foundation(&self, idx: usize) -> Result<&[Card], MoveError> {
+        self.foundations
+            .get(idx)
+            .map(Vec::as_slice)
+            .ok_or(MoveError::NoSuchSpot)
+    }
+
+    pub fn cell(&self, idx: usize) -> Result<&[Card], MoveError> {
+        self.cells
+            .get(idx)
+            .map(Vec::as_slice)
+            .ok_or(MoveError::NoSuchSpot)
+    }
+
+    pub fn check(&self, m: Move) -> Result<(), MoveError> {
+        self.variant().check(self, m)
+    }
+
+    pub fn apply(&self, m: Move) -> Result<GameState, MoveError> {
+        self.check(m)?;
+
+        let mut next = self.clone();
+        let outcome = match m {
+            Move::PileToPile { from, n, to } => {
+                next.move_cards(Region::Pile(from), n, Region::Pile(to))
+            }
+            Move::PileToFoundation { from, to } => next.move_cards(
+                Region::Pile(from),
+                1,
+                Region::Foundation(to),
+            ),
+            Move::FoundationToPile { from, to } => next.move_cards(
+                Region::Foundation(from),
+                1,
+                Region::Pile(to),
+            ),
+            Move::WasteToPile { to } => {
+                next.move_cards(Region::Waste, 1, Region::Pile(to))
+            }
+            Move::WasteToFoundation { to } => {
+                next.move_cards(Region::Waste, 1, Region::Foundation(to))
+            }
+            Move::PileToCell { from, to } => {
+                next.move_cards(Region::Pile(from), 1, Region::Cell(to))
+            }
+            Move::CellToPile { from, to } => {
+                next.move_cards(Region::Cell(from), 1, Region::Pile(to))
+            }
+            Move::CellToFoundation { from, to } => next.move_cards(
+                Region::Cell(from),
+                1,
+                Region::Foundation(to),
+            ),
+            Move::Draw => self.variant().draw(&mut next),
+        };
+
+        self.variant().settle(self, &mut next, m, outcome);
+        next.moves += 1;
+        Ok(next)
+    }
+
+    fn region(&mut self, region: Region) -> &mut Vec<Card> {
+        match region {
+            Region::Waste => &mut self.waste,
+            Region::Pile(idx) => &mut self.piles[idx],
+            Region::Foundation(idx) => &mut self.foundations[idx],
+            Region::Cell(idx) => &mut self.cells[idx],
+        }
+    }
+
+    fn move_cards(
+        &mut self,
+        from: Region,
+        n: usize,
+        to: Region,
+    ) -> Outcome {
+        let source = self.region(from);
+        let cards: Vec<_> = source.drain(source.len() - n..).collect();
+        let mut outcome = Outcome::default();
+        if let Region::Pile(_) = from {
+            if let Some(card) = source.last_mut() {
+                outcome.flipped = !card.is_faceup();
+                card.flip();
+            }
+        }
+
+        self.region(to).extend(cards);
+        outcome
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Region {
+    Waste,
+    Pile(usize),
+    Foundation(usize),
+    Cell(usize),
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::game::{DrawMode, VariantKind};
+
+    // A face-up card from a short name such as "10H" or "KS"
+    pub(crate) fn card(name: &str) -> Card {
+        let mut card = down(name);
+        card.flip();
+        card
+    }
+
+    pub(crate) fn down(name: &str) -> Card {
+        let (rank, suit) = name.split_at(name.len() - 1);
+        let rank = match rank {
+            "A" => "ace",
+            "J" => "jack",
+            "Q" => "queen",
+            "K" => "king",
+            rank => rank,
+        };
+        let suit = match suit {
+            "S" => "spades",
+            "H" => "hearts",
+            "D" => "diamonds",
+            "C" => "clubs",
+            _ => panic!("Invalid suit"),
+        };
+        Card::new(format!("{rank}_of_{suit}.png"))
+    }
+
+    pub(crate) fn cards(names: &str) -> Vec<Card> {
+        names.split_whitespace().map(card).collect()
+    }
+
+    // An empty Klondike board to lay out by hand
+    pub(crate) fn board(rules: Rules) -> GameState {
+        GameState {
+            seed: 0,
+            rules,
+            deck: Vec::new(),
+            waste: Vec::new(),
+            piles: vec![Vec::new(); 7],
+            foundations: vec![Vec::new(); 4],
+            cells: Vec::new(),
+            recycles: 0,
+            score: 0,
+            moves: 0,
+            elapsed: 0,
+        }
+    }
+
+    const RANKS: [&str; 13] = [
+        "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+    ];
+
+    fn suit(suit: char) -> Vec<Card> {
+        RANKS
+            .iter()
+            .map(|rank| card(&format!("{rank}{suit}")))
+            .collect()
+    }
+
+    // One of each card, face down, as `fetch_cards` reads them
+    pub(crate) fn full_deck() -> Vec<Card> {
+        "SHDC"
+            .chars()
+            .flat_map(|s| RANKS.map(|rank| down(&format!("{rank}{s}"))))
+            .collect()
+    }
+
+    pub(crate) fn names(cards: &[Card]) -> Vec<String> {
+        cards.iter().map(Card::name).collect()
+    }
+
+    #[test]
+    fn deals_a_klondike_layout() {
+        let deck = full_deck();
+        let state =
+            GameState::new(deck.clone(), Some(7), Rules::default());
+
+        let lengths: Vec<_> = state.piles.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(state.deck.len(), 24);
+        for pile in &state.piles {
+            let faceup =
+                pile.iter().filter(|card| card.is_faceup()).count();
+            assert_eq!(faceup, 1);
+            assert!(pile.last().unwrap().is_faceup());
+        }
+        assert_eq!(GameState::new(deck, Some(7), Rules::default()), state);
+    }
+
+    // Seeds are shared, so a deal must never change under a dependency bump
+    #[test]
+    fn deals_the_same_cards_for_a_seed() {
+        let state = GameState::new(full_deck(), Some(1), Rules::default());
+        let tops: Vec<_> = state
+            .piles
+            .iter()
+            .filter_map(|p| p.last().cloned())
+            .collect();
+        assert_eq!(names(&tops), names(&cards("3C 3H AH 8S 2H AS 9C")));
+    }
+
+    #[test]
+    fn rejects_moves_off_the_board() {
+        let mut state = board(Rules::default());
+        state.piles[0] = cards("KH");
+        state.waste = cards("AS");
+        for m in [
+            Move::PileToPile {
+                from: 0,
+                n: 1,
+                to: 7,
+            },
+            Move::PileToPile {
+                from: 7,
+                n: 1,
+                to: 0,
+            },
+            Move::PileToFoundation { from: 0, to: 4 },
+            Move::FoundationToPile { from: 4, to: 0 },
+            Move::WasteToFoundation { to: 4 },
+        ] {
+            assert_eq!(
+                state.apply(m),
+                Err(MoveError::NoSuchSpot),
+                "{m:?}"
+            );
+        }
+
+        let spider = Rules {
+            variant: VariantKind::Spider(Default::default()),
+            ..Default::default()
+        };
+        let state = GameState::new(full_deck(), Some(1), spider);
+        let m = Move::PileToPile {
+            from: 0,
+            n: 1,
+            to: 10,
+        };
+        assert_eq!(state.apply(m), Err(MoveError::NoSuchSpot));
+
+        let freecell = Rules {
+            variant: VariantKind::FreeCell,
+            ..Default::default()
+        };
+        let state = GameState::new(full_deck(), Some(1), freecell);
+        for m in [
+            Move::PileToCell { from: 0, to: 4 },
+            Move::CellToPile { from: 4, to: 0 },
+            Move::CellToFoundation { from: 4, to: 0 },
+        ] {
+            assert_eq!(
+                state.apply(m),
+                Err(MoveError::NoSuchSpot),
+                "{m:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn moves_a_stack_between_piles() {
+        let mut state = board(Rules::default());
+        state.piles[0] = vec![down("2C"), card("9H"), card("8S")];
+        state.piles[1] = cards("10C");
+
+        let m = Move::PileToPile {
+            from: 0,
+            n: 2,
+            to: 1,
+        };
+        let next = state.apply(m).unwrap();
+        assert_eq!(names(&next.piles[1]), names(&cards("10C 9H 8S")));
+        assert_eq!(next.piles[0], cards("2C"));
+        assert_eq!(next.moves, 1);
+        assert_eq!(state.piles[0].len(), 3, "apply leaves the original");
+    }
+
+    #[test]
+    fn turns_over_the_exposed_card() {
+        let mut state = board(Rules::default());
+        state.piles[0] = vec![down("5D"), card("AS")];
+
+        let m = Move::PileToFoundation { from: 0, to: 2 };
+        let next = state.apply(m).unwrap();
+        assert_eq!(next.piles[0], cards("5D"));
+        assert_eq!(next.foundations[2], cards("AS"));
+        // Standard scoring: 10 to the foundation and 5 for the flip
+        assert_eq!(next.score, 15);
+    }
+
+    #[test]
+    fn moves_a_card_off_a_foundation() {
+        let mut state = board(Rules::default());
+        state.foundations[0] = cards("AH 2H");
+        state.piles[3] = cards("3S");
+
+        let m = Move::FoundationToPile { from: 0, to: 3 };
+        let next = state.apply(m).unwrap();
+        assert_eq!(next.foundations[0], cards("AH"));
+        assert_eq!(next.piles[3], cards("3S 2H"));
+    }
+
+    #[test]
+    fn plays_from_the_waste() {
+        let mut state = board(Rules::default());
+        state.waste = cards("QD AC");
+        state.piles[0] = cards("KS");
+
+        let next = state.apply(Move::WasteToFoundation { to: 1 }).unwrap();
+        assert_eq!(next.foundations[1], cards("AC"));
+        let next = next.apply(Move::WasteToPile { to: 0 }).unwrap();
+        assert_eq!(next.piles[0], cards("KS QD"));
+        assert!(next.waste.is_empty());
+        assert_eq!(next.moves, 2);
+    }
+
+    #[test]
+    fn draws_one_card_face_up() {
+        let mut state = board(Rules::default());
+        state.deck = vec![down("2H"), down("3H"), down("4H")];
+
+        let next = state.apply(Move::Draw).unwrap();
+        assert_eq!(next.waste, cards("4H"));
+        assert_eq!(next.deck, vec![down("2H"), down("3H")]);
+    }
+
+    #[test]
+    fn draws_three_cards_with_the_third_on_top() {
+        let rules = Rules {
+            draw: DrawMode::Three,
+            ..Default::default()
+        };
+        let mut state = board(rules);
+        state.deck = vec![down("2H"), down("3H"), down("4H"), down("5H")];
+
+        let next = state.apply(Move::Draw).unwrap();
+        assert_eq!(next.waste, cards("5H 4H 3H"));
+        assert_eq!(next.deck, vec![down("2H")]);
+
+        // Fewer than three left draws what there is
+        let next = next.apply(Move::Draw).unwrap();
+        assert_eq!(next.waste, cards("5H 4H 3H 2H"));
+        assert!(next.deck.is_empty());
+    }
+
+    #[test]
+    fn recycles_the_waste_in_order() {
+        let mut state = board(Rules::default());
+        state.deck = vec![down("2H"), down("3H")];
+
+        let drawn = state
+            .apply(Move::Draw)
+            .and_then(|state| state.apply(Move::Draw))
+            .unwrap();
+        let recycled = drawn.apply(Move::Draw).unwrap();
+        assert!(recycled.waste.is_empty());
+        assert_eq!(recycled.recycles, 1);
+        let again = recycled.apply(Move::Draw).unwrap();
+        assert_eq!(again.waste, cards("3H"));
+        // Turning the deck over costs 100, but standard scores stop at 0
+        assert_eq!(recycled.score, 0);
+    }
+
+    #[test]
+    fn rejected_moves_leave_the_state_alone() {
+        let state = board(Rules::default());
+        assert_eq!(
+            state.apply(Move::WasteToPile { to: 0 }),
+            Err(MoveError::EmptySource)
+        );
+        assert_eq!(state.moves, 0);
+    }
+
+    #[test]
+    fn wins_with_full_foundations() {
+        let mut state = board(Rules::default());
+        assert!(!state.is_won());
+        for (foundation, s) in
+            state.foundations.iter_mut().zip("SHDC".chars())
+        {
+            *foundation = suit(s);
+        }
+        assert!(state.is_won());
+        assert!(!state.is_clock_running());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::game::{GameState, Rules};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    Won,
+    Abandoned,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub rules: Rules,
+    pub result: GameResult,
+    pub elapsed: u64,
+    pub moves: u32,
+    pub score: i32,
+}
+
+impl GameRecord {
+    pub fn new(state: &GameState) -> Self {
+        Self {
+            seed: state.seed,
+            rules: state.rules,
+            result: if state.is_won() {
+                GameResult::Won
+            } else {
+                GameResult::Abandoned
+            },
+            elapsed: state.elapsed,
+            moves: state.moves,
+            score: state.score,
+        }
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.result == GameResult::Won
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModeStats {
+    pub rules: Rules,
+    pub played: u32,
+    pub won: u32,
+    pub best_time: Option<u64>,
+    pub best_score: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub modes: Vec<ModeStats>,
+}
+
+impl Stats {
+    // Records are expected oldest first
+    pub fn new(records: &[GameRecord]) -> Self {
+        let mut stats = Stats::default();
+        for record in records {
+            stats.played += 1;
+            if record.is_won() {
+                stats.won += 1;
+                stats.current_streak += 1;
+                stats.longest_streak =
+                    stats.longest_streak.max(stats.current_streak);
+            } else {
+                stats.current_streak = 0;
+            }
+
+            let mode = match stats
+                .modes
+                .iter_mut()
+                .position(|mode| mode.rules == record.rules)
+            {
+                Some(idx) => &mut stats.modes[idx],
+                None => {
+                    stats.modes.push(ModeStats {
+                        rules: record.rules,
+                        played: 0,
+                        won: 0,
+                        best_time: None,
+                        best_score: None,
+                    });
+                    stats.modes.last_mut().unwrap()
+                }
+            };
+            mode.played += 1;
+            mode.best_score = mode.best_score.max(Some(record.score));
+            if record.is_won() {
+                mode.won += 1;
+                mode.best_time =
+                    Some(mode.best_time.map_or(record.elapsed, |best| {
+                        best.min(record.elapsed)
+                    }));
+            }
+        }
+
+        stats
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        match self.played {
+            0 => 0.0,
+            played => f64::from(self.won) / f64::from(played),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::DrawMode;
+
+    fn draw_three() -> Rules {
+        Rules {
+            draw: DrawMode::Three,
+            ..Default::default()
+        }
+    }
+
+    fn record(
+        rules: Rules,
+        result: GameResult,
+        elapsed: u64,
+    ) -> GameRecord {
+        GameRecord {
+            seed: 1,
+            rules,
+            result,
+            elapsed,
+            moves: 100,
+            score: elapsed as i32,
+        }
+    }
+
+    fn won(elapsed: u64) -> GameRecord {
+        record(Rules::default(), GameResult::Won, elapsed)
+    }
+
+    fn lost() -> GameRecord {
+        record(Rules::default(), GameResult::Abandoned, 60)
+    }
+
+    #[test]
+    fn resets_the_streak_on_a_loss() {
+        let stats = Stats::new(&[won(1), won(2), won(3), lost(), won(4)]);
+        assert_eq!((stats.played, stats.won), (5, 4));
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.longest_streak, 3);
+        assert_eq!(stats.win_rate(), 0.8);
+    }
+
+    #[test]
+    fn keeps_the_longest_streak_once_it_is_beaten() {
+        let stats = Stats::new(&[won(1), lost(), won(2), won(3)]);
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.longest_streak, 2);
+        assert_eq!(Stats::new(&[lost()]).longest_streak, 0);
+    }
+
+    #[test]
+    fn tracks_best_times_per_mode() {
+        let stats = Stats::new(&[
+            won(300),
+            record(draw_three(), GameResult::Won, 500),
+            won(200),
+            record(draw_three(), GameResult::Abandoned, 100),
+            won(250),
+        ]);
+
+        let [one, three] = stats.modes.as_slice() else {
+            panic!("expected two modes, got {:?}", stats.modes);
+        };
+        assert_eq!(one.rules, Rules::default());
+        assert_eq!((one.played, one.won), (3, 3));
+        assert_eq!(one.best_time, Some(200));
+        assert_eq!(three.rules, draw_three());
+        assert_eq!((three.played, three.won), (2, 1));
+        assert_eq!(three.best_time, Some(500));
+        assert_eq!(three.best_score, Some(500));
+    }
+}
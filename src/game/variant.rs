@@ -0,0 +1,101 @@
+use crate::game::{Card, GameState, Move, MoveError, Outcome, Rules};
+use rand::{prelude::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Everything that differs between solitaire games. `GameState` holds the
+// board and applies moves; a variant decides how the board is dealt, which
+// moves are legal, what the stock does and when the game is won. Options
+// such as Klondike's draw count stay on `Rules`, which every method can
+// reach through the state.
+
+pub struct Layout {
+    pub piles: Vec<Vec<Card>>,
+    pub foundations: Vec<Vec<Card>>,
+    pub cells: Vec<Vec<Card>>,
+}
+
+pub trait Variant {
+    // Builds the cards for a deal from one full pack
+    fn deck(&self, cards: Vec<Card>, _rules: &Rules) -> Vec<Card> {
+        cards
+    }
+
+    fn random_seed(&self) -> u64 {
+        rand::random()
+    }
+
+    // Seeds that don't name a deal are replaced with a random one
+    fn is_valid_seed(&self, _seed: u64) -> bool {
+        true
+    }
+
+    // Seeds end up in shared links and saved games, so this needs an RNG
+    // whose output is pinned down, unlike `StdRng`'s
+    fn shuffle(&self, cards: &mut Vec<Card>, seed: u64) {
+        cards.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    }
+
+    // Deals from the front of `cards`; whatever is left becomes the stock.
+    // The top card of each pile is turned face up afterwards.
+    fn layout(&self, cards: &mut Vec<Card>) -> Layout;
+
+    fn initial_score(&self, _rules: &Rules) -> i32 {
+        0
+    }
+
+    fn check(&self, state: &GameState, m: Move) -> Result<(), MoveError>;
+
+    fn has_stock(&self) -> bool {
+        true
+    }
+
+    fn has_waste(&self) -> bool {
+        false
+    }
+
+    // What `Move::Draw` does, once `check` has allowed it
+    fn draw(&self, _state: &mut GameState) -> Outcome {
+        Outcome::default()
+    }
+
+    fn is_stock_exhausted(&self, state: &GameState) -> bool {
+        state.deck.is_empty()
+    }
+
+    // Runs after every move, with `next` already showing the move: tidies
+    // the board and sets the new score
+    fn settle(
+        &self,
+        _previous: &GameState,
+        _next: &mut GameState,
+        _m: Move,
+        _outcome: Outcome,
+    ) {
+    }
+
+    fn is_won(&self, state: &GameState) -> bool {
+        state
+            .foundations
+            .iter()
+            .all(|foundation| foundation.len() == 13)
+    }
+
+    // Which foundation `card` could go to, if any
+    fn foundation_for(
+        &self,
+        _state: &GameState,
+        _card: &Card,
+    ) -> Option<usize> {
+        None
+    }
+
+    // Whether the rest of the game can be played out automatically
+    fn can_auto_complete(&self, _state: &GameState) -> bool {
+        false
+    }
+
+    // The next move when playing out the rest of the game
+    fn next_auto_complete_move(&self, _state: &GameState) -> Option<Move> {
+        None
+    }
+}
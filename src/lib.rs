@@ -1,9 +1,12 @@
 use cfg_if::cfg_if;
 pub mod app;
 pub mod components;
+#[cfg(feature = "ssr")]
+pub mod db;
 pub mod error_template;
 pub mod fileserv;
 pub mod game;
+pub mod storage;
 
 cfg_if! { if #[cfg(feature = "hydrate")] {
     use leptos::*;
@@ -0,0 +1,58 @@
+use cfg_if::cfg_if;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+// Thin wrappers around the browser's localStorage. They are no-ops on the
+// server, where there is no storage to talk to.
+
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    cfg_if! { if #[cfg(feature = "hydrate")] {
+        let storage = leptos::window().local_storage().ok()??;
+        let value = storage.get_item(key).ok()??;
+        serde_json::from_str(&value).ok()
+    } else {
+        _ = key;
+        None
+    }}
+}
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("This browser has no local storage")]
+    Unavailable,
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    // Usually the storage quota running out
+    #[error("The browser refused to store {0}")]
+    Rejected(String),
+}
+
+pub fn save<T: Serialize>(
+    key: &str,
+    value: &T,
+) -> Result<(), StorageError> {
+    cfg_if! { if #[cfg(feature = "hydrate")] {
+        let storage = leptos::window()
+            .local_storage()
+            .ok()
+            .flatten()
+            .ok_or(StorageError::Unavailable)?;
+        let value = serde_json::to_string(value)?;
+        storage
+            .set_item(key, &value)
+            .map_err(|_| StorageError::Rejected(key.to_owned()))
+    } else {
+        _ = (key, value);
+        Ok(())
+    }}
+}
+
+pub fn remove(key: &str) {
+    cfg_if! { if #[cfg(feature = "hydrate")] {
+        if let Ok(Some(storage)) = leptos::window().local_storage() {
+            _ = storage.remove_item(key);
+        }
+    } else {
+        _ = key;
+    }}
+}